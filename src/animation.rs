@@ -0,0 +1,87 @@
+use std::f32::consts::PI;
+use std::rc::Rc;
+use crate::texture::Texture;
+
+// Filas de una hoja direccional, una cada 45 grados
+pub const DIRECTIONS: usize = 8;
+
+// Qué hace la animación al pasar su último cuadro
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Playback {
+    Loop, // Vuelve al primer cuadro
+    Once, // Se queda en el último cuadro
+}
+
+// Secuencia de cuadros a ritmo fijo
+#[derive(Clone, Copy, Debug)]
+pub struct Clip {
+    pub frames: usize,
+    pub frame_secs: f32, // Duración de cada cuadro en segundos
+    pub playback: Playback,
+}
+
+impl Clip {
+    // Cuadro que se muestra a los `time` segundos de empezar
+    pub fn frame_at(&self, time: f32) -> usize {
+        let frame = (time.max(0.0) / self.frame_secs) as usize;
+        match self.playback {
+            Playback::Loop => frame % self.frames,
+            Playback::Once => frame.min(self.frames - 1),
+        }
+    }
+}
+
+// Cuadros recortados de una cuadrícula de la misma imagen. Las columnas son los cuadros del
+// clip; una hoja con DIRECTIONS filas es direccional y cada fila muestra el sprite visto
+// desde otro ángulo, empezando de frente.
+pub struct SpriteSheet {
+    frames: Vec<Rc<Texture>>, // Fila por fila
+    columns: usize,
+    rows: usize,
+    pub clip: Clip,
+}
+
+impl SpriteSheet {
+    pub fn from_grid(texture: &Texture, columns: usize, rows: usize, frame_secs: f32, playback: Playback) -> Self {
+        let frame_width = (texture.width / columns).max(1);
+        let frame_height = (texture.height / rows).max(1);
+        let mut frames = Vec::with_capacity(columns * rows);
+
+        for row in 0..rows {
+            for column in 0..columns {
+                let (left, top) = (column * frame_width, row * frame_height);
+                let texels = (0..frame_width * frame_height)
+                    .map(|i| texture.get_rgba(left + i % frame_width, top + i / frame_width))
+                    .collect();
+                frames.push(Rc::new(Texture::from_texels(frame_width, frame_height, texels)));
+            }
+        }
+
+        Self { frames, columns, rows, clip: Clip { frames: columns, frame_secs, playback } }
+    }
+
+    // Hoja de un solo cuadro, para usar una imagen fija donde se espera una animación
+    pub fn still(texture: Rc<Texture>) -> Self {
+        Self {
+            frames: vec![texture],
+            columns: 1,
+            rows: 1,
+            clip: Clip { frames: 1, frame_secs: 1.0, playback: Playback::Loop },
+        }
+    }
+
+    // Cuadro del clip a los `time` segundos en la fila `direction`; las hojas no direccionales
+    // usan siempre su primera fila
+    pub fn frame(&self, time: f32, direction: usize) -> &Rc<Texture> {
+        let row = if self.rows == DIRECTIONS { direction % DIRECTIONS } else { 0 };
+        &self.frames[row * self.columns + self.clip.frame_at(time)]
+    }
+}
+
+// Fila de la hoja direccional para un sprite que mira hacia `heading` visto desde el ángulo
+// `to_viewer` (del sprite hacia la cámara): 0 es de frente y cada fila gira 45 grados más
+pub fn direction_index(heading: f32, to_viewer: f32) -> usize {
+    let step = 2.0 * PI / DIRECTIONS as f32;
+    let angle = (to_viewer - heading).rem_euclid(2.0 * PI);
+    (angle / step + 0.5) as usize % DIRECTIONS
+}
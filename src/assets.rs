@@ -0,0 +1,245 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::Arc;
+use crate::animation::{Playback, SpriteSheet};
+use crate::maze::{load_maze, Level, MazeError};
+use crate::texture::Texture;
+
+// Tipos de recurso que se pueden declarar en el manifiesto
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum AssetKind {
+    Texture,
+    Sound,
+    Level,
+    Sheet, // Textura dividida en cuadros de animación
+}
+
+#[derive(Debug)]
+pub enum AssetError {
+    Manifest(String),                // Línea inválida en el manifiesto
+    Io(PathBuf, io::Error),
+    Image(PathBuf, image::ImageError),
+    Level(PathBuf, MazeError),
+}
+
+impl fmt::Display for AssetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AssetError::Manifest(message) => write!(f, "manifiesto inválido: {}", message),
+            AssetError::Io(path, err) => write!(f, "{}: {}", path.display(), err),
+            AssetError::Image(path, err) => write!(f, "{}: {}", path.display(), err),
+            AssetError::Level(path, err) => write!(f, "{}: {}", path.display(), err),
+        }
+    }
+}
+
+impl Error for AssetError {}
+
+// Cómo se recorta y se anima una hoja de sprites: `<columnas>x<filas> <segundos por cuadro> [loop|once]`
+struct SheetLayout {
+    columns: usize,
+    rows: usize,
+    frame_secs: f32,
+    playback: Playback,
+}
+
+impl SheetLayout {
+    fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.split_whitespace();
+        let (columns, rows) = parts.next()?.split_once('x')?;
+        let (columns, rows) = (columns.parse().ok()?, rows.parse().ok()?);
+        let frame_secs: f32 = parts.next()?.parse().ok()?;
+        let playback = match parts.next() {
+            None | Some("loop") => Playback::Loop,
+            Some("once") => Playback::Once,
+            Some(_) => return None,
+        };
+        (columns > 0 && rows > 0 && frame_secs > 0.0).then_some(Self { columns, rows, frame_secs, playback })
+    }
+}
+
+// Carga texturas, sonidos y niveles por nombre y guarda en caché los que ya se leyeron.
+// Los nombres se buscan en el manifiesto del directorio de recursos; un nombre que no
+// aparece ahí se usa directamente como ruta, así los niveles antiguos siguen funcionando.
+pub struct AssetManager {
+    root: PathBuf,
+    manifest: HashMap<(AssetKind, String), PathBuf>,
+    textures: HashMap<String, Rc<Texture>>,
+    sounds: HashMap<String, Arc<[u8]>>,
+    layouts: HashMap<String, SheetLayout>,
+    sheets: HashMap<String, Rc<SpriteSheet>>,
+}
+
+impl AssetManager {
+    pub fn new(root: &str) -> Self {
+        Self {
+            root: PathBuf::from(root),
+            manifest: HashMap::new(),
+            textures: HashMap::new(),
+            sounds: HashMap::new(),
+            layouts: HashMap::new(),
+            sheets: HashMap::new(),
+        }
+    }
+
+    // Lee `<root>/manifest.txt`, con líneas `<tipo> <nombre> = <ruta relativa a root>`;
+    // las hojas de sprites agregan su cuadrícula después de la ruta. `#` inicia un comentario
+    pub fn from_manifest(root: &str) -> Result<Self, AssetError> {
+        let mut assets = Self::new(root);
+        let path = assets.root.join("manifest.txt");
+        let contents = fs::read_to_string(&path).map_err(|err| AssetError::Io(path.clone(), err))?;
+
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let invalid = || AssetError::Manifest(format!("línea {}: {}", index + 1, line));
+
+            let (key, value) = line.split_once('=').ok_or_else(invalid)?;
+            let (kind, name) = key.trim().split_once(' ').ok_or_else(invalid)?;
+            let kind = match kind {
+                "texture" => AssetKind::Texture,
+                "sound" => AssetKind::Sound,
+                "level" => AssetKind::Level,
+                "sheet" => AssetKind::Sheet,
+                _ => return Err(invalid()),
+            };
+            let name = name.trim().to_string();
+            let value = value.trim();
+
+            let path = if kind == AssetKind::Sheet {
+                let (path, layout) = value.split_once(' ').ok_or_else(invalid)?;
+                assets.layouts.insert(name.clone(), SheetLayout::parse(layout).ok_or_else(invalid)?);
+                path
+            } else {
+                value
+            };
+            assets.manifest.insert((kind, name), assets.root.join(path));
+        }
+
+        Ok(assets)
+    }
+
+    // Ruta del archivo al que se refiere un nombre
+    pub fn resolve(&self, kind: AssetKind, name: &str) -> PathBuf {
+        self.manifest
+            .get(&(kind, name.to_string()))
+            .cloned()
+            .unwrap_or_else(|| PathBuf::from(name))
+    }
+
+    pub fn texture(&mut self, name: &str) -> Result<Rc<Texture>, AssetError> {
+        if let Some(texture) = self.textures.get(name) {
+            return Ok(Rc::clone(texture));
+        }
+
+        let path = self.resolve(AssetKind::Texture, name);
+        let texture = Rc::new(Texture::from_file(&path.to_string_lossy()).map_err(|err| AssetError::Image(path, err))?);
+        self.textures.insert(name.to_string(), Rc::clone(&texture));
+        Ok(texture)
+    }
+
+    // Como `texture`, pero si falla avisa por consola y usa un tablero en su lugar
+    pub fn texture_or_missing(&mut self, name: &str) -> Rc<Texture> {
+        self.texture(name).unwrap_or_else(|err| {
+            eprintln!("No se pudo cargar la textura {}: {}", name, err);
+            let missing = Rc::new(Texture::checkerboard());
+            self.textures.insert(name.to_string(), Rc::clone(&missing));
+            missing
+        })
+    }
+
+    // Descarta las copias en caché de la textura o de la hoja de sprites, para que el
+    // próximo pedido las vuelva a leer del disco
+    pub fn invalidate_texture(&mut self, name: &str) {
+        self.textures.remove(name);
+        self.sheets.remove(name);
+    }
+
+    pub fn is_sheet(&self, name: &str) -> bool {
+        self.layouts.contains_key(name)
+    }
+
+    // Hoja de sprites declarada en el manifiesto, recortada según su cuadrícula
+    pub fn sheet(&mut self, name: &str) -> Result<Rc<SpriteSheet>, AssetError> {
+        if let Some(sheet) = self.sheets.get(name) {
+            return Ok(Rc::clone(sheet));
+        }
+
+        let layout = self
+            .layouts
+            .get(name)
+            .ok_or_else(|| AssetError::Manifest(format!("{} no es una hoja de sprites", name)))?;
+        let path = self.resolve(AssetKind::Sheet, name);
+        let texture = Texture::from_file(&path.to_string_lossy()).map_err(|err| AssetError::Image(path, err))?;
+        let sheet = Rc::new(SpriteSheet::from_grid(
+            &texture,
+            layout.columns,
+            layout.rows,
+            layout.frame_secs,
+            layout.playback,
+        ));
+        self.sheets.insert(name.to_string(), Rc::clone(&sheet));
+        Ok(sheet)
+    }
+
+    // Como `sheet`, pero si falla avisa por consola y usa la textura del mismo nombre como
+    // un único cuadro
+    pub fn sheet_or_still(&mut self, name: &str) -> Rc<SpriteSheet> {
+        self.sheet(name).unwrap_or_else(|err| {
+            eprintln!("No se pudo cargar la animación {}: {}", name, err);
+            let still = Rc::new(SpriteSheet::still(self.texture_or_missing(name)));
+            self.sheets.insert(name.to_string(), Rc::clone(&still));
+            still
+        })
+    }
+
+    // Bytes del archivo de sonido, listos para decodificarse con rodio
+    pub fn sound(&mut self, name: &str) -> Result<Arc<[u8]>, AssetError> {
+        if let Some(sound) = self.sounds.get(name) {
+            return Ok(Arc::clone(sound));
+        }
+
+        let path = self.resolve(AssetKind::Sound, name);
+        let sound: Arc<[u8]> = fs::read(&path).map_err(|err| AssetError::Io(path, err))?.into();
+        self.sounds.insert(name.to_string(), Arc::clone(&sound));
+        Ok(sound)
+    }
+
+    // Los niveles no se guardan en caché: cada llamada devuelve una copia nueva del archivo
+    pub fn level(&self, name: &str) -> Result<Level, AssetError> {
+        let path = self.resolve(AssetKind::Level, name);
+        load_maze(&path.to_string_lossy()).map_err(|err| AssetError::Level(path, err))
+    }
+
+    // Texturas de pared de la leyenda de un nivel, por carácter; de las animadas se toma
+    // el primer cuadro
+    pub fn legend_textures(&mut self, legend: &HashMap<char, String>) -> HashMap<char, Rc<Texture>> {
+        legend
+            .iter()
+            .map(|(glyph, name)| {
+                let texture = if self.is_sheet(name) {
+                    Rc::clone(self.sheet_or_still(name).frame(0.0, 0))
+                } else {
+                    self.texture_or_missing(name)
+                };
+                (*glyph, texture)
+            })
+            .collect()
+    }
+
+    // Animaciones de la leyenda de un nivel, solo para los caracteres que usan una hoja de sprites
+    pub fn legend_sheets(&mut self, legend: &HashMap<char, String>) -> HashMap<char, Rc<SpriteSheet>> {
+        let animated: Vec<(&char, &String)> = legend.iter().filter(|(_, name)| self.is_sheet(name)).collect();
+        animated
+            .into_iter()
+            .map(|(glyph, name)| (*glyph, self.sheet_or_still(name)))
+            .collect()
+    }
+}
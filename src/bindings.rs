@@ -0,0 +1,185 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use minifb::{Key, MouseMode, Window};
+use crate::input::Input;
+
+// Acciones del jugador que se pueden asignar a teclas
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Action {
+    Forward,
+    Back,
+    StrafeLeft,
+    StrafeRight,
+    TurnLeft,
+    TurnRight,
+    ToggleView,
+    Pause,
+    Flashlight,
+}
+
+impl Action {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "forward" => Some(Action::Forward),
+            "back" => Some(Action::Back),
+            "strafe_left" => Some(Action::StrafeLeft),
+            "strafe_right" => Some(Action::StrafeRight),
+            "turn_left" => Some(Action::TurnLeft),
+            "turn_right" => Some(Action::TurnRight),
+            "toggle_view" => Some(Action::ToggleView),
+            "pause" => Some(Action::Pause),
+            "flashlight" => Some(Action::Flashlight),
+            _ => None,
+        }
+    }
+}
+
+// Teclas que se pueden nombrar en el archivo de configuración
+const NAMED_KEYS: [Key; 58] = [
+    Key::A, Key::B, Key::C, Key::D, Key::E, Key::F, Key::G, Key::H, Key::I, Key::J,
+    Key::K, Key::L, Key::M, Key::N, Key::O, Key::P, Key::Q, Key::R, Key::S, Key::T,
+    Key::U, Key::V, Key::W, Key::X, Key::Y, Key::Z,
+    Key::Key0, Key::Key1, Key::Key2, Key::Key3, Key::Key4,
+    Key::Key5, Key::Key6, Key::Key7, Key::Key8, Key::Key9,
+    Key::Up, Key::Down, Key::Left, Key::Right,
+    Key::Space, Key::Enter, Key::Tab, Key::Backspace,
+    Key::LeftShift, Key::RightShift, Key::LeftCtrl, Key::RightCtrl, Key::LeftAlt, Key::RightAlt,
+    Key::Comma, Key::Period, Key::Semicolon, Key::Apostrophe, Key::Slash, Key::Minus,
+    Key::Pause, Key::CapsLock,
+];
+
+// Convierte el nombre de una tecla ("W", "Up", "LeftShift", "1"...) en la tecla de minifb
+fn parse_key(name: &str) -> Option<Key> {
+    let name = if name.len() == 1 && name.chars().all(|c| c.is_ascii_digit()) {
+        format!("Key{}", name)
+    } else {
+        name.to_string()
+    };
+    NAMED_KEYS
+        .iter()
+        .copied()
+        .find(|key| format!("{:?}", key).eq_ignore_ascii_case(&name))
+}
+
+// Ajustes del giro con el ratón
+pub struct MouseLook {
+    pub enabled: bool,
+    pub sensitivity: f32, // Radianes por píxel de desplazamiento
+    pub invert: bool,
+}
+
+pub struct Bindings {
+    keys: HashMap<Action, Vec<Key>>,
+    pub mouse: MouseLook,
+    last_mouse_x: Option<f32>,
+}
+
+impl Bindings {
+    // Controles originales del juego: W/S avanzan, A/D giran, Q/E se desplazan, M cambia la vista
+    pub fn new() -> Self {
+        Self {
+            keys: HashMap::from([
+                (Action::Forward, vec![Key::W]),
+                (Action::Back, vec![Key::S]),
+                (Action::StrafeLeft, vec![Key::Q]),
+                (Action::StrafeRight, vec![Key::E]),
+                (Action::TurnLeft, vec![Key::A]),
+                (Action::TurnRight, vec![Key::D]),
+                (Action::ToggleView, vec![Key::M]),
+                (Action::Pause, vec![Key::P]),
+                (Action::Flashlight, vec![Key::F]),
+            ]),
+            mouse: MouseLook {
+                enabled: false,
+                sensitivity: 0.005,
+                invert: false,
+            },
+            last_mouse_x: None,
+        }
+    }
+
+    // Lee las asignaciones `accion = Tecla, Tecla` y los ajustes del ratón de un archivo.
+    // Las acciones que no aparecen conservan sus teclas por defecto.
+    pub fn from_file(path: &str) -> io::Result<Self> {
+        let mut bindings = Self::new();
+        let invalid = |line: usize, message: String| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("{}:{}: {}", path, line, message))
+        };
+
+        for (index, line) in fs::read_to_string(path)?.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| invalid(index + 1, "se esperaba `clave = valor`".to_string()))?;
+            let (key, value) = (key.trim(), value.trim());
+
+            match key {
+                "mouse_look" => {
+                    bindings.mouse.enabled = value
+                        .parse()
+                        .map_err(|_| invalid(index + 1, format!("valor inválido: {}", value)))?
+                }
+                "mouse_sensitivity" => {
+                    bindings.mouse.sensitivity = value
+                        .parse()
+                        .map_err(|_| invalid(index + 1, format!("valor inválido: {}", value)))?
+                }
+                "mouse_invert" => {
+                    bindings.mouse.invert = value
+                        .parse()
+                        .map_err(|_| invalid(index + 1, format!("valor inválido: {}", value)))?
+                }
+                _ => {
+                    let action = Action::from_name(key)
+                        .ok_or_else(|| invalid(index + 1, format!("acción desconocida: {}", key)))?;
+                    let keys = value
+                        .split(',')
+                        .map(|name| {
+                            parse_key(name.trim())
+                                .ok_or_else(|| invalid(index + 1, format!("tecla desconocida: {}", name.trim())))
+                        })
+                        .collect::<io::Result<Vec<Key>>>()?;
+                    bindings.keys.insert(action, keys);
+                }
+            }
+        }
+
+        Ok(bindings)
+    }
+
+    // La acción está activa mientras alguna de sus teclas esté presionada
+    pub fn is_down(&self, input: &Input, action: Action) -> bool {
+        self.keys_for(action).iter().any(|key| input.is_down(*key))
+    }
+
+    // La acción se activó en este cuadro (sin repetición al mantener la tecla)
+    pub fn is_pressed(&self, input: &Input, action: Action) -> bool {
+        self.keys_for(action).iter().any(|key| input.is_pressed(*key))
+    }
+
+    // Giro en radianes según el desplazamiento horizontal del ratón desde la última llamada
+    pub fn mouse_turn(&mut self, window: &Window) -> f32 {
+        let mouse_x = window.get_mouse_pos(MouseMode::Pass).map(|(x, _)| x);
+        let previous = self.last_mouse_x;
+        self.last_mouse_x = mouse_x;
+
+        if !self.mouse.enabled {
+            return 0.0;
+        }
+        match (previous, mouse_x) {
+            (Some(previous), Some(current)) => {
+                let turn = (current - previous) * self.mouse.sensitivity;
+                if self.mouse.invert { -turn } else { turn }
+            }
+            _ => 0.0,
+        }
+    }
+
+    fn keys_for(&self, action: Action) -> &[Key] {
+        self.keys.get(&action).map_or(&[], |keys| keys.as_slice())
+    }
+}
@@ -0,0 +1,103 @@
+use std::fs;
+use std::io;
+use std::time::Duration;
+use crate::assets::{AssetError, AssetManager};
+use crate::maze::Level;
+use crate::mazegen::{generate_level, GeneratorOptions};
+
+// Origen de cada nivel de la campaña
+pub enum LevelSource {
+    File(String), // Nombre de nivel del manifiesto o ruta del archivo
+    Generated(GeneratorOptions),
+}
+
+// Estadísticas que se acumulan de un nivel al siguiente
+pub struct CampaignStats {
+    pub levels_completed: usize,
+    pub deaths: usize,
+    pub total_time: Duration,
+}
+
+pub struct Campaign {
+    levels: Vec<LevelSource>,
+    current: usize,
+    pub stats: CampaignStats,
+}
+
+impl Campaign {
+    pub fn new(levels: Vec<LevelSource>) -> Self {
+        Self {
+            levels,
+            current: 0,
+            stats: CampaignStats {
+                levels_completed: 0,
+                deaths: 0,
+                total_time: Duration::ZERO,
+            },
+        }
+    }
+
+    // Lee la lista ordenada de niveles (nombres o rutas), uno por línea; `#` inicia un comentario
+    pub fn from_file(path: &str) -> io::Result<Self> {
+        let levels: Vec<LevelSource> = fs::read_to_string(path)?
+            .lines()
+            .map(|line| line.split('#').next().unwrap_or("").trim())
+            .filter(|line| !line.is_empty())
+            .map(|line| LevelSource::File(line.to_string()))
+            .collect();
+
+        if levels.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "la campaña no tiene niveles"));
+        }
+
+        Ok(Self::new(levels))
+    }
+
+    pub fn load_current(&self, assets: &AssetManager) -> Result<Level, AssetError> {
+        match &self.levels[self.current] {
+            LevelSource::File(name) => assets.level(name),
+            LevelSource::Generated(options) => Ok(generate_level(options)),
+        }
+    }
+
+    // Nombre del nivel actual para los mensajes de error
+    pub fn current_name(&self) -> String {
+        match &self.levels[self.current] {
+            LevelSource::File(path) => path.clone(),
+            LevelSource::Generated(options) => format!("{} #{}", options.algorithm, options.seed),
+        }
+    }
+
+    // Nombre o ruta del nivel actual si viene de un archivo
+    pub fn current_file(&self) -> Option<&str> {
+        match &self.levels[self.current] {
+            LevelSource::File(name) => Some(name),
+            LevelSource::Generated(_) => None,
+        }
+    }
+
+    pub fn level_count(&self) -> usize {
+        self.levels.len()
+    }
+
+    pub fn is_last_level(&self) -> bool {
+        self.current + 1 >= self.levels.len()
+    }
+
+    pub fn record_death(&mut self, time: Duration) {
+        self.stats.deaths += 1;
+        self.stats.total_time += time;
+    }
+
+    // Registra el nivel superado y avanza al siguiente; devuelve false si ya no quedan niveles
+    pub fn complete_level(&mut self, time: Duration) -> bool {
+        self.stats.levels_completed += 1;
+        self.stats.total_time += time;
+
+        if self.is_last_level() {
+            return false;
+        }
+        self.current += 1;
+        true
+    }
+}
@@ -1,3 +1,4 @@
+#[derive(Clone, Copy)]
 pub struct Color {
     pub r: u8,
     pub g: u8,
@@ -9,7 +10,11 @@ impl Color {
         Self { r, g, b} 
     }
 
-    pub fn to_hex(&self) -> u32 {
+    pub fn from_hex(hex: u32) -> Self {
+        Self::new((hex >> 16) as u8, (hex >> 8) as u8, hex as u8)
+    }
+
+    pub fn to_hex(self) -> u32 {
         ((self.r as u32) << 16) | ((self.g as u32) << 8) | (self.b as u32)
     }
 
@@ -29,15 +34,22 @@ impl Color {
         Self::new(0, 0, 0)
     }
 
-    pub fn gradient_sky(ratio: f32) -> Self {
-        let start_color = Color::new(0, 0, 0);
-        let end_color = Color::new(32, 30, 67);
+    // Mezcla un color 0xAARRGGBB hacia `target` conservando su alfa
+    pub fn mix_hex(color: u32, target: Color, ratio: f32) -> u32 {
+        (color & 0xFF000000) | Self::gradient(Self::from_hex(color), target, ratio).to_hex()
+    }
+
+    // Multiplica un color 0xAARRGGBB por la luz (r, g, b) recibida, conservando su alfa
+    pub fn modulate_hex(color: u32, light: [f32; 3]) -> u32 {
+        let channel = |shift: u32, amount: f32| (((color >> shift) & 0xFF) as f32 * amount).min(255.0) as u32;
+        (color & 0xFF000000) | (channel(16, light[0]) << 16) | (channel(8, light[1]) << 8) | channel(0, light[2])
+    }
 
+    pub fn gradient(start_color: Color, end_color: Color, ratio: f32) -> Self {
         Self::new(
             (start_color.r as f32 * (1.0 - ratio) + end_color.r as f32 * ratio) as u8,
             (start_color.g as f32 * (1.0 - ratio) + end_color.g as f32 * ratio) as u8,
             (start_color.b as f32 * (1.0 - ratio) + end_color.b as f32 * ratio) as u8,
         )
     }
-
 }
@@ -1,50 +1,174 @@
-use minifb::{Window, Key};
+use minifb::Window;
+use nalgebra_glm::Vec2;
 use std::f32::consts::PI;
+use crate::bindings::{Action, Bindings};
+use crate::input::Input;
 use crate::player::Player;
+use crate::maze::{is_walkable, position_to_cell};
 
-pub fn process_events(window: &Window, player: &mut Player, maze: &Vec<Vec<char>>, block_size: usize) {
-    const MOVE_SPEED: f32 = 4.0;
-    const ROTATION_SPEED: f32 = PI / 12.0;
+// `dt` es el tiempo simulado en segundos; las velocidades no dependen de la tasa de cuadros
+pub fn process_events(input: &Input, bindings: &Bindings, player: &mut Player, maze: &[Vec<char>], block_size: usize, dt: f32) {
+    const MOVE_SPEED: f32 = 180.0;          // Unidades por segundo
+    const ROTATION_SPEED: f32 = PI * 1.5;   // Radianes por segundo
 
-    // Rotación con las teclas A y D
-    if window.is_key_down(Key::A) {
-        player.a -= ROTATION_SPEED;
+    let step = MOVE_SPEED * dt;
+    let rotation = ROTATION_SPEED * dt;
+
+    // Rotación (A y D por defecto)
+    if bindings.is_down(input, Action::TurnLeft) {
+        player.a -= rotation;
     }
-    if window.is_key_down(Key::D) {
-        player.a += ROTATION_SPEED;
+    if bindings.is_down(input, Action::TurnRight) {
+        player.a += rotation;
     }
 
     let mut new_x = player.position.x;
     let mut new_y = player.position.y;
 
-    // Movimiento hacia adelante con W
-    if window.is_key_down(Key::W) {
-        new_x += player.a.cos() * MOVE_SPEED;
-        new_y += player.a.sin() * MOVE_SPEED;
+    // Movimiento hacia adelante (W por defecto)
+    if bindings.is_down(input, Action::Forward) {
+        new_x += player.a.cos() * step;
+        new_y += player.a.sin() * step;
+    }
+    // Movimiento hacia atrás (S por defecto)
+    if bindings.is_down(input, Action::Back) {
+        new_x -= player.a.cos() * step;
+        new_y -= player.a.sin() * step;
+    }
+
+    // Movimiento lateral hacia la izquierda (Q por defecto), en la dirección `a - PI/2`:
+    // la primera columna de la vista 3D
+    if bindings.is_down(input, Action::StrafeLeft) {
+        new_x += player.a.sin() * step;
+        new_y -= player.a.cos() * step;
+    }
+    // Movimiento lateral hacia la derecha (E por defecto), en la dirección `a + PI/2`
+    if bindings.is_down(input, Action::StrafeRight) {
+        new_x -= player.a.sin() * step;
+        new_y += player.a.cos() * step;
+    }
+
+    // Resolver cada eje por separado para deslizarse a lo largo de las paredes
+    let target = Vec2::new(new_x, player.position.y);
+    player.position = move_until_blocked(maze, block_size, player.radius, player.position, target);
+    let target = Vec2::new(player.position.x, new_y);
+    player.position = move_until_blocked(maze, block_size, player.radius, player.position, target);
+}
+
+// Giro con el ratón; se aplica una vez por cuadro porque depende del desplazamiento del cursor
+pub fn process_mouse_look(window: &Window, bindings: &mut Bindings, player: &mut Player) {
+    player.a += bindings.mouse_turn(window);
+}
+
+// Avanza de `from` hacia `to` tanto como se pueda sin que el círculo toque una pared
+fn move_until_blocked(maze: &[Vec<char>], block_size: usize, radius: f32, from: Vec2, to: Vec2) -> Vec2 {
+    if !collides(maze, block_size, radius, to) {
+        return to;
     }
-    // Movimiento hacia atrás con S
-    if window.is_key_down(Key::S) {
-        new_x -= player.a.cos() * MOVE_SPEED;
-        new_y -= player.a.sin() * MOVE_SPEED;
+
+    // Si ya empieza dentro de una pared (nivel recargado, radio cambiado), se permite
+    // cualquier paso que no lo hunda más, para que pueda salir en lugar de quedar congelado
+    if collides(maze, block_size, radius, from) {
+        let reach = radius * 2.0;
+        return if wall_distance(maze, block_size, reach, to) >= wall_distance(maze, block_size, reach, from) {
+            to
+        } else {
+            from
+        };
     }
 
-    // Movimiento lateral hacia la izquierda con Q
-    if window.is_key_down(Key::Q) {
-        new_x -= player.a.sin() * MOVE_SPEED;
-        new_y += player.a.cos() * MOVE_SPEED;
+    // Búsqueda binaria del punto de contacto para no quedar separado de la pared
+    let mut free = from;
+    let mut blocked = to;
+    for _ in 0..6 {
+        let middle = (free + blocked) * 0.5;
+        if collides(maze, block_size, radius, middle) {
+            blocked = middle;
+        } else {
+            free = middle;
+        }
     }
-    // Movimiento lateral hacia la derecha con E
-    if window.is_key_down(Key::E) {
-        new_x += player.a.sin() * MOVE_SPEED;
-        new_y -= player.a.cos() * MOVE_SPEED;
+    free
+}
+
+// Comprueba si el círculo del jugador se superpone con alguna celda sólida (fuera del mapa cuenta como pared)
+pub fn collides(maze: &[Vec<char>], block_size: usize, radius: f32, center: Vec2) -> bool {
+    wall_distance(maze, block_size, radius, center) < radius
+}
+
+// Distancia del punto a la celda sólida más cercana, mirando solo hasta `reach`; devuelve
+// `reach` si no hay ninguna a ese alcance
+fn wall_distance(maze: &[Vec<char>], block_size: usize, reach: f32, center: Vec2) -> f32 {
+    let block = block_size as f32;
+    let (min_i, min_j) = position_to_cell(center - Vec2::new(reach, reach), block_size);
+    let (max_i, max_j) = position_to_cell(center + Vec2::new(reach, reach), block_size);
+    let mut nearest = reach;
+
+    for j in min_j..=max_j {
+        for i in min_i..=max_i {
+            if is_walkable(maze, i, j) {
+                continue;
+            }
+
+            // Punto de la celda más cercano al centro del círculo; cubre también las esquinas
+            let closest = Vec2::new(
+                center.x.clamp(i as f32 * block, (i + 1) as f32 * block),
+                center.y.clamp(j as f32 * block, (j + 1) as f32 * block),
+            );
+            nearest = nearest.min((closest - center).norm());
+        }
+    }
+    nearest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::Level;
+
+    fn corridor(block_size: usize) -> Level {
+        let grid = ["#######", "#     #", "#######"].iter().map(|row| row.chars().collect()).collect();
+        let mut level = Level::new("test", grid);
+        level.block_size = block_size;
+        level.spawn = (1, 1);
+        level
+    }
+
+    #[test]
+    fn player_fits_in_small_blocks() {
+        for block_size in [10, 20, 50, 100] {
+            let level = corridor(block_size);
+            let radius = level.player_radius();
+            let start = level.spawn_position();
+            assert!(!collides(&level.grid, block_size, radius, start), "bloque {}", block_size);
+
+            let target = start + Vec2::new(block_size as f32, 0.0);
+            let moved = move_until_blocked(&level.grid, block_size, radius, start, target);
+            assert_eq!(moved, target, "bloque {}", block_size);
+        }
+    }
+
+    #[test]
+    fn slides_to_the_wall_without_crossing_it() {
+        let level = corridor(50);
+        let radius = level.player_radius();
+        let start = level.spawn_position();
+        let moved = move_until_blocked(&level.grid, 50, radius, start, start - Vec2::new(0.0, 40.0));
+        assert!(!collides(&level.grid, 50, radius, moved));
+        assert!(moved.y < start.y && moved.y - radius >= 50.0 - 1.0);
     }
 
-    // Verificar si la nueva posición no está dentro de una pared
-    let i = (new_x as usize) / block_size;
-    let j = (new_y as usize) / block_size;
+    #[test]
+    fn can_leave_a_wall_it_starts_in() {
+        let level = corridor(20);
+        let radius = level.player_radius();
+        // Pegado a la pared de arriba, con el círculo superpuesto
+        let stuck = Vec2::new(30.0, 21.0);
+        assert!(collides(&level.grid, 20, radius, stuck));
 
-    if maze[j][i] == ' ' {
-        player.position.x = new_x;
-        player.position.y = new_y;
+        let away = move_until_blocked(&level.grid, 20, radius, stuck, stuck + Vec2::new(0.0, 2.0));
+        assert_eq!(away, stuck + Vec2::new(0.0, 2.0));
+        let deeper = move_until_blocked(&level.grid, 20, radius, stuck, stuck - Vec2::new(0.0, 2.0));
+        assert_eq!(deeper, stuck);
     }
 }
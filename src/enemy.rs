@@ -1,13 +1,38 @@
+use std::collections::VecDeque;
 use nalgebra_glm::Vec2;
+use crate::pathfinding::Cell;
+
+// Estado de comportamiento de cada fantasma
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GhostState {
+    Wander, // Deambula entre celdas aleatorias cercanas
+    Patrol, // Recorre una ruta fija de celdas
+    Chase,  // Persigue al jugador mientras lo ve
+    Flee,   // Huye cuando el jugador lo mira de frente
+}
 
 pub struct Enemy {
     pub position: Vec2,
+    pub heading: f32,             // Dirección de movimiento en radianes
+    pub state: GhostState,
+    pub path: VecDeque<Cell>,     // Celdas pendientes hasta el destino actual
+    pub patrol_route: Vec<Cell>,
+    pub patrol_index: usize,
+    pub state_timer: f32,         // Segundos restantes de huida o de búsqueda tras perder al jugador
+    pub repath_timer: f32,        // Segundos hasta recalcular el camino de persecución
 }
 
 impl Enemy {
     pub fn new(x: f32, y: f32) -> Self {
         Self {
             position: Vec2::new(x, y),
+            heading: 0.0,
+            state: GhostState::Wander,
+            path: VecDeque::new(),
+            patrol_route: Vec::new(),
+            patrol_index: 0,
+            state_timer: 0.0,
+            repath_timer: 0.0,
         }
     }
 
@@ -15,4 +40,30 @@ impl Enemy {
         // Comprobar si el enemigo está lo suficientemente cerca del jugador como para colisionar
         (self.position - player_pos).norm() < distance_threshold
     }
+
+    // Avanza hacia el centro de la siguiente celda del camino sin pasarse
+    pub fn follow_path(&mut self, speed: f32, dt: f32, block_size: usize) {
+        let block = block_size as f32;
+        let mut remaining = speed * dt;
+
+        while remaining > 0.0 {
+            let Some(&(col, row)) = self.path.front() else {
+                break;
+            };
+
+            let target = Vec2::new((col as f32 + 0.5) * block, (row as f32 + 0.5) * block);
+            let delta = target - self.position;
+            let distance = delta.norm();
+
+            if distance <= remaining {
+                self.position = target;
+                self.path.pop_front();
+                remaining -= distance;
+            } else {
+                self.heading = delta.y.atan2(delta.x);
+                self.position += delta / distance * remaining;
+                remaining = 0.0;
+            }
+        }
+    }
 }
@@ -1,5 +1,5 @@
 use std::time::{Instant, Duration};
-use crate::framebuffer::Framebuffer;
+use crate::framebuffer::{BlendMode, Canvas, FramebufferView};
 use crate::color::Color;
 
 pub struct FPSCounter {
@@ -27,20 +27,29 @@ impl FPSCounter {
         }
     }
 
-    pub fn render(&self, framebuffer: &mut Framebuffer, x: usize, y: usize, scale: usize) {
+    pub fn render(&self, framebuffer: &mut FramebufferView, x: usize, y: usize, scale: usize) {
         let fps_string = format!("FPS: {}", self.fps);
-        render_text(framebuffer, &fps_string, x, y, scale);
+
+        // Fondo semitransparente para que el texto se lea sobre cualquier escena
+        let (x, y, scale) = (x as i32, y as i32, scale as i32);
+        let text_width = fps_string.len() as i32 * 4 * scale;
+        framebuffer.set_blend_mode(BlendMode::Alpha);
+        framebuffer.set_current_rgba(0x80000000);
+        framebuffer.fill_rect(x - scale, y - scale, text_width + scale, 7 * scale);
+        framebuffer.set_blend_mode(BlendMode::Opaque);
+
+        render_text(framebuffer, &fps_string, x as usize, y as usize, scale as usize);
     }
 }
 
-fn render_text(framebuffer: &mut Framebuffer, text: &str, x: usize, y: usize, scale: usize) {
+pub fn render_text(framebuffer: &mut FramebufferView, text: &str, x: usize, y: usize, scale: usize) {
     
     let mut offset_x = x;
     
     for ch in text.chars() {
         if let Some(digit) = ch.to_digit(10) {
             render_digit(framebuffer, digit as usize, offset_x, y, scale);
-        } else if ch == ':' || ch == ' ' || ch == 'F' || ch == 'P' || ch == 'S' {
+        } else if ch == ':' || ch == '/' || ch == ' ' || ch == 'F' || ch == 'P' || ch == 'S' {
             // Render placeholders for FPS letters and colon
             render_symbol(framebuffer, ch, offset_x, y, scale);
         }
@@ -48,7 +57,7 @@ fn render_text(framebuffer: &mut Framebuffer, text: &str, x: usize, y: usize, sc
     }
 }
 
-fn render_digit(framebuffer: &mut Framebuffer, digit: usize, x: usize, y: usize, scale: usize) {
+fn render_digit(framebuffer: &mut FramebufferView, digit: usize, x: usize, y: usize, scale: usize) {
     const DIGIT_MAP: [[u8; 15]; 10] = [
         // Dígito 0
         [
@@ -137,23 +146,20 @@ fn render_digit(framebuffer: &mut Framebuffer, digit: usize, x: usize, y: usize,
     for row in 0..5 {
         for col in 0..3 {
             if DIGIT_MAP[digit][row * 3 + col] == 1 {
-                for i in 0..scale {
-                    for j in 0..scale {
-                        framebuffer.set_current_color(color);
-                        framebuffer.point(x + col * scale + i, y + row * scale + j);
-                    }
-                }
+                framebuffer.set_current_color(color);
+                framebuffer.fill_rect((x + col * scale) as i32, (y + row * scale) as i32, scale as i32, scale as i32);
             }
         }
     }
 }
 
-fn render_symbol(framebuffer: &mut Framebuffer, symbol: char, x: usize, y: usize, scale: usize) {
-    const SYMBOL_MAP: [([u8; 15], char); 4] = [
+fn render_symbol(framebuffer: &mut FramebufferView, symbol: char, x: usize, y: usize, scale: usize) {
+    const SYMBOL_MAP: [([u8; 15], char); 5] = [
         ([1, 1, 1, 1, 0, 0, 1, 1, 1, 1, 0, 0, 1, 0, 0], 'F'), // F
         ([1, 1, 1, 1, 0, 1, 1, 1, 1, 1, 0, 0, 1, 0, 0], 'P'), // P
         ([1, 1, 1, 1, 0, 0, 1, 1, 1, 0, 0, 1, 1, 1, 1], 'S'), // S
         ([0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0], ':'), // ':'
+        ([0, 0, 1, 0, 0, 1, 0, 1, 0, 1, 0, 0, 1, 0, 0], '/'), // '/'
     ];
 
     let color = Color::white().to_hex();
@@ -162,12 +168,8 @@ fn render_symbol(framebuffer: &mut Framebuffer, symbol: char, x: usize, y: usize
         for row in 0..5 {
             for col in 0..3 {
                 if symbol_data[row * 3 + col] == 1 {
-                    for i in 0..scale {
-                        for j in 0..scale {
-                            framebuffer.set_current_color(color);
-                            framebuffer.point(x + col * scale + i, y + row * scale + j);
-                        }
-                    }
+                    framebuffer.set_current_color(color);
+                    framebuffer.fill_rect((x + col * scale) as i32, (y + row * scale) as i32, scale as i32, scale as i32);
                 }
             }
         }
@@ -1,9 +1,74 @@
+use crate::texture::Texture;
+
+#[derive(Clone, Copy)]
+pub struct Viewport { // Rectángulo del framebuffer donde dibuja un renderizador
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Viewport {
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Self { x, y, width, height }
+    }
+
+    pub fn split_horizontal(&self) -> (Viewport, Viewport) { // Divide el rectángulo en mitad izquierda y derecha
+        let left_width = self.width / 2;
+        (
+            Viewport::new(self.x, self.y, left_width, self.height),
+            Viewport::new(self.x + left_width, self.y, self.width - left_width, self.height),
+        )
+    }
+
+    // Intersección con otro rectángulo; queda vacío (ancho o alto 0) si no se tocan
+    pub fn intersect(&self, other: &Viewport) -> Viewport {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let right = (self.x + self.width).min(other.x + other.width);
+        let bottom = (self.y + self.height).min(other.y + other.height);
+        Viewport::new(x, y, right.saturating_sub(x), bottom.saturating_sub(y))
+    }
+}
+
+// Forma de combinar el color que se dibuja con el que ya hay en el framebuffer
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BlendMode {
+    Opaque,   // Reemplaza el píxel e ignora el alfa
+    Alpha,    // Mezcla según el alfa del color
+    Additive, // Suma el color ponderado por su alfa (brillos, destellos)
+}
+
+impl BlendMode {
+    // Combina un color 0xAARRGGBB sobre un píxel 0xRRGGBB del framebuffer
+    pub fn apply(self, destination: u32, source: u32) -> u32 {
+        let alpha = source >> 24;
+        match self {
+            BlendMode::Opaque => source & 0xFFFFFF,
+            BlendMode::Alpha => match alpha {
+                0 => destination,
+                255 => source & 0xFFFFFF,
+                _ => combine_channels(destination, source, |d, s| (s * alpha + d * (255 - alpha)) / 255),
+            },
+            BlendMode::Additive => combine_channels(destination, source, |d, s| (d + s * alpha / 255).min(255)),
+        }
+    }
+}
+
+fn combine_channels(destination: u32, source: u32, combine: impl Fn(u32, u32) -> u32) -> u32 {
+    [16, 8, 0].iter().fold(0, |color, shift| {
+        let channel = combine((destination >> shift) & 0xFF, (source >> shift) & 0xFF);
+        color | (channel << shift)
+    })
+}
+
 pub struct Framebuffer { // Estructura pública (puede utilizarse en otros módulos)
     pub width: usize, 
     pub height: usize, 
     pub buffer: Vec<u32>, // Guarda un vector de valores de pixeles, donde cada elemento representa un color en formato u32 (un entero de 32 bits sin signo).
     background_color: u32, // Guarda el fondo del framebuffer
-    current_color: u32, // Guarda el color actual que se usará para dibujar
+    current_color: u32, // Guarda el color actual que se usará para dibujar, en formato 0xAARRGGBB
+    blend_mode: BlendMode,
 }
 
 impl Framebuffer{
@@ -13,7 +78,8 @@ impl Framebuffer{
             height,
             buffer: vec![0; width * height], // Inicializa el buffer con ceros
             background_color: 0x000000, 
-            current_color: 0xFFFFFF,    
+            current_color: 0xFFFFFFFF,
+            blend_mode: BlendMode::Opaque,
         } // Retorna una nueva instancia del Framebuffer
     }
 
@@ -26,15 +92,271 @@ impl Framebuffer{
     pub fn point(&mut self, x: usize, y: usize) { // Dibuja un punto en el framebuffer
         if x < self.width && y < self.height {
             let index = y * self.width + x;
-            self.buffer[index] = self.current_color;
+            self.buffer[index] = self.blend_mode.apply(self.buffer[index], self.current_color);
         }
     }
 
+    pub fn viewport(&self) -> Viewport { // Rectángulo que cubre todo el framebuffer
+        Viewport::new(0, 0, self.width, self.height)
+    }
+
+    pub fn view(&mut self, viewport: Viewport) -> FramebufferView<'_> { // Vista para dibujar dentro de un rectángulo, recortado a los bordes del framebuffer
+        let viewport = viewport.intersect(&self.viewport());
+        FramebufferView { framebuffer: self, viewport }
+    }
+
     pub fn set_background_color(&mut self, color: u32) { // Establece el color de fondo del framebuffer
         self.background_color = color;
     }
 
-    pub fn set_current_color(&mut self, color: u32) { //Establece el color actual para dibujar en el framebuffer
+}
+
+// Región rectangular de un framebuffer con su propio origen de coordenadas.
+// Todo lo que se dibuja fuera del rectángulo se descarta.
+pub struct FramebufferView<'a> {
+    framebuffer: &'a mut Framebuffer,
+    viewport: Viewport,
+}
+
+impl FramebufferView<'_> {
+    pub fn point(&mut self, x: usize, y: usize) { // Dibuja un punto relativo a la esquina de la vista
+        if x < self.viewport.width && y < self.viewport.height {
+            self.framebuffer.point(self.viewport.x + x, self.viewport.y + y);
+        }
+    }
+
+    pub fn view(&mut self, viewport: Viewport) -> FramebufferView<'_> { // Subvista con coordenadas relativas a esta vista
+        let absolute = Viewport::new(
+            self.viewport.x + viewport.x,
+            self.viewport.y + viewport.y,
+            viewport.width,
+            viewport.height,
+        );
+        FramebufferView {
+            viewport: absolute.intersect(&self.viewport),
+            framebuffer: self.framebuffer,
+        }
+    }
+}
+
+impl Canvas for Framebuffer {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    fn current_color(&self) -> u32 {
+        self.current_color
+    }
+
+    fn set_current_rgba(&mut self, color: u32) { //Establece el color actual para dibujar en el framebuffer
         self.current_color = color;
     }
-}
\ No newline at end of file
+
+    fn blend_mode(&self) -> BlendMode {
+        self.blend_mode
+    }
+
+    fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.blend_mode = mode;
+    }
+
+    fn row_mut(&mut self, y: usize) -> &mut [u32] {
+        &mut self.buffer[y * self.width..(y + 1) * self.width]
+    }
+}
+
+impl Canvas for FramebufferView<'_> {
+    fn width(&self) -> usize {
+        self.viewport.width
+    }
+
+    fn height(&self) -> usize {
+        self.viewport.height
+    }
+
+    fn current_color(&self) -> u32 {
+        self.framebuffer.current_color
+    }
+
+    fn set_current_rgba(&mut self, color: u32) {
+        self.framebuffer.current_color = color;
+    }
+
+    fn blend_mode(&self) -> BlendMode {
+        self.framebuffer.blend_mode
+    }
+
+    fn set_blend_mode(&mut self, mode: BlendMode) {
+        self.framebuffer.blend_mode = mode;
+    }
+
+    fn row_mut(&mut self, y: usize) -> &mut [u32] {
+        let Viewport { x, width, .. } = self.viewport;
+        &mut self.framebuffer.row_mut(self.viewport.y + y)[x..x + width]
+    }
+}
+
+// Primitivas de dibujo 2D sobre cualquier superficie con filas de píxeles.
+// Las coordenadas son con signo para poder dibujar figuras que salen por los bordes;
+// todo se recorta a la superficie antes de escribir.
+pub trait Canvas {
+    fn width(&self) -> usize;
+    fn height(&self) -> usize;
+    fn current_color(&self) -> u32; // Color actual en formato 0xAARRGGBB
+    fn set_current_rgba(&mut self, color: u32);
+    fn blend_mode(&self) -> BlendMode;
+    fn set_blend_mode(&mut self, mode: BlendMode);
+    fn row_mut(&mut self, y: usize) -> &mut [u32]; // Fila `y` completa; `y` debe estar dentro de la superficie
+
+    // Color 0xRRGGBB completamente opaco
+    fn set_current_color(&mut self, color: u32) {
+        self.set_current_rgba(0xFF000000 | (color & 0xFFFFFF));
+    }
+
+    // Tramo horizontal de x0 a x1 (ambos incluidos) en la fila y
+    fn hspan(&mut self, x0: i32, x1: i32, y: i32) {
+        let (x0, x1) = (x0.min(x1), x0.max(x1));
+        if y < 0 || y >= self.height() as i32 || x1 < 0 || x0 >= self.width() as i32 {
+            return;
+        }
+        let color = self.current_color();
+        let mode = self.blend_mode();
+        let x0 = x0.max(0) as usize;
+        let x1 = (x1 as usize).min(self.width() - 1);
+        let pixels = &mut self.row_mut(y as usize)[x0..=x1];
+        if mode == BlendMode::Opaque {
+            pixels.fill(color & 0xFFFFFF);
+        } else {
+            for pixel in pixels {
+                *pixel = mode.apply(*pixel, color);
+            }
+        }
+    }
+
+    // Tramo vertical de y0 a y1 (ambos incluidos) en la columna x
+    fn vspan(&mut self, x: i32, y0: i32, y1: i32) {
+        let (y0, y1) = (y0.min(y1).max(0), y0.max(y1).min(self.height() as i32 - 1));
+        if x < 0 || x >= self.width() as i32 {
+            return;
+        }
+        let color = self.current_color();
+        let mode = self.blend_mode();
+        for y in y0..=y1 {
+            let pixel = &mut self.row_mut(y as usize)[x as usize];
+            *pixel = mode.apply(*pixel, color);
+        }
+    }
+
+    // Línea de Bresenham entre dos puntos
+    fn line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32) {
+        let dx = (x1 - x0).abs();
+        let dy = -(y1 - y0).abs();
+        let step_x = if x0 < x1 { 1 } else { -1 };
+        let step_y = if y0 < y1 { 1 } else { -1 };
+        let (mut x, mut y) = (x0, y0);
+        let mut error = dx + dy;
+
+        loop {
+            self.hspan(x, x, y);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                x += step_x;
+            }
+            if doubled <= dx {
+                error += dx;
+                y += step_y;
+            }
+        }
+    }
+
+    fn fill_rect(&mut self, x: i32, y: i32, width: i32, height: i32) {
+        if width <= 0 {
+            return;
+        }
+        for row in y.max(0)..(y + height).min(self.height() as i32) {
+            self.hspan(x, x + width - 1, row);
+        }
+    }
+
+    // Contorno de un rectángulo de un píxel de grosor
+    fn rect(&mut self, x: i32, y: i32, width: i32, height: i32) {
+        if width <= 0 || height <= 0 {
+            return;
+        }
+        self.hspan(x, x + width - 1, y);
+        self.hspan(x, x + width - 1, y + height - 1);
+        self.vspan(x, y, y + height - 1);
+        self.vspan(x + width - 1, y, y + height - 1);
+    }
+
+    // Contorno de un círculo con el algoritmo del punto medio
+    fn circle(&mut self, cx: i32, cy: i32, radius: i32) {
+        let (mut x, mut y) = (radius, 0);
+        let mut error = 1 - radius;
+        while x >= y {
+            for (px, py) in [(x, y), (y, x), (-y, x), (-x, y), (-x, -y), (-y, -x), (y, -x), (x, -y)] {
+                self.hspan(cx + px, cx + px, cy + py);
+            }
+            y += 1;
+            if error < 0 {
+                error += 2 * y + 1;
+            } else {
+                x -= 1;
+                error += 2 * (y - x) + 1;
+            }
+        }
+    }
+
+    fn fill_circle(&mut self, cx: i32, cy: i32, radius: i32) {
+        for dy in -radius..=radius {
+            let half_width = ((radius * radius - dy * dy) as f32).sqrt() as i32;
+            self.hspan(cx - half_width, cx + half_width, cy + dy);
+        }
+    }
+
+    // Copia una textura escalada al rectángulo indicado, combinando cada texel según el
+    // modo de mezcla actual. Los píxeles iguales a `color_key` (0xRRGGBB) no se dibujan.
+    fn blit(&mut self, texture: &Texture, x: i32, y: i32, width: i32, height: i32, color_key: Option<u32>) {
+        self.blit_mapped(texture, x, y, width, height, color_key, |color| color);
+    }
+
+    // Como `blit`, pero cada texel pasa por `map` antes de dibujarse (niebla, tintes)
+    #[allow(clippy::too_many_arguments)]
+    fn blit_mapped(
+        &mut self,
+        texture: &Texture,
+        x: i32,
+        y: i32,
+        width: i32,
+        height: i32,
+        color_key: Option<u32>,
+        map: impl Fn(u32) -> u32,
+    ) {
+        if width <= 0 || height <= 0 {
+            return;
+        }
+        let mode = self.blend_mode();
+        let x0 = x.max(0);
+        let x1 = (x + width).min(self.width() as i32);
+        for row in y.max(0)..(y + height).min(self.height() as i32) {
+            let texture_y = ((row - y) as usize * texture.height) / height as usize;
+            let pixels = self.row_mut(row as usize);
+            for column in x0..x1 {
+                let texture_x = ((column - x) as usize * texture.width) / width as usize;
+                let color = texture.get_rgba(texture_x, texture_y);
+                if Some(color & 0xFFFFFF) != color_key {
+                    let pixel = &mut pixels[column as usize];
+                    *pixel = mode.apply(*pixel, map(color));
+                }
+            }
+        }
+    }
+}
@@ -1,52 +1,229 @@
-use crate::enemy::Enemy;
+use crate::enemy::{Enemy, GhostState};
+use crate::maze::{is_walkable, position_to_cell, GhostBehavior, GhostRules};
+use crate::pathfinding::{find_path, Cell};
+use crate::player::Player;
+use crate::raycaster::has_line_of_sight;
 use nalgebra_glm::Vec2;
-use std::time::{Instant, Duration};
 use rand::Rng;
 
+// Segundos que un fantasma sigue buscando al jugador después de perderlo de vista
+const LOSE_SIGHT_SECS: f32 = 3.0;
+// Segundos que dura la huida cuando el jugador lo mira de frente
+const FLEE_SECS: f32 = 2.5;
+// Ángulo (en radianes) dentro del cual el jugador está mirando al fantasma
+const STARE_ANGLE: f32 = 0.15;
+// Cada cuánto se recalcula el camino hacia el jugador durante la persecución
+const REPATH_SECS: f32 = 0.4;
+// Número de puntos de la ruta de patrulla
+const PATROL_POINTS: usize = 4;
+
 pub struct GhostManager {
-    respawn_timer: Instant,
-    respawn_duration: Duration,
+    respawn_timer: f32,    // Segundos simulados desde la última reaparición
+    respawn_duration: f32,
+    min_distance: f32,
+    max_distance: f32,
+    behavior: GhostBehavior,
+    speed: f32,
+    sight: f32,
 }
 
 impl GhostManager {
-    pub fn new() -> Self {
+    pub fn new(rules: &GhostRules) -> Self {
         Self {
-            respawn_timer: Instant::now(),
-            respawn_duration: Duration::from_secs(7), 
+            respawn_timer: 0.0,
+            respawn_duration: rules.respawn_secs,
+            min_distance: rules.min_distance,
+            max_distance: rules.max_distance,
+            behavior: rules.behavior,
+            speed: rules.speed,
+            sight: rules.sight,
         }
     }
 
-    pub fn update_ghosts(&mut self, player_position: Vec2, maze: &Vec<Vec<char>>, enemies: &mut Vec<Enemy>, block_size: usize) {
-        // Solo respawnear los fantasmas cuando el temporizador expira
-        if self.respawn_timer.elapsed() >= self.respawn_duration {
-            self.respawn_timer = Instant::now(); // Reiniciar el temporizador
-
-            let mut rng = rand::thread_rng();
+    // `dt` es el tiempo simulado en segundos desde la última actualización
+    pub fn update_ghosts(&mut self, player: &Player, maze: &[Vec<char>], enemies: &mut [Enemy], block_size: usize, dt: f32) {
+        let block = block_size as f32;
+        let mut rng = rand::thread_rng();
+        let (player_i, player_j) = position_to_cell(player.position, block_size);
+        let player_cell = (player_i.max(0) as usize, player_j.max(0) as usize);
 
-            for enemy in enemies.iter_mut() {
-                // Elegir una nueva posición cercana al jugador
-                loop {
-                    let min_distance = block_size as f32 * 3.0; // Distancia mínima al jugador
-                    let max_distance = block_size as f32 * 9.0; // Distancia máxima al jugador
+        for enemy in enemies.iter_mut() {
+            let to_enemy = enemy.position - player.position;
+            let distance = to_enemy.norm();
 
-                    let distance = rng.gen_range(min_distance..max_distance);
-                    let angle = rng.gen_range(0.0..(2.0 * std::f32::consts::PI));
+            let sees_player = distance < self.sight * block
+                && has_line_of_sight(maze, enemy.position, player.position, block_size);
 
-                    // Calcular nuevas posiciones basadas en la distancia y el ángulo
-                    let x = player_position.x + distance * angle.cos();
-                    let y = player_position.y + distance * angle.sin();
+            // El jugador lo está mirando si el fantasma queda casi en el centro de su vista
+            let angle_to_enemy = to_enemy.y.atan2(to_enemy.x);
+            let stare = (angle_to_enemy - player.a).sin().abs() < STARE_ANGLE.sin()
+                && (angle_to_enemy - player.a).cos() > 0.0;
 
-                    let i = (x as usize) / block_size;
-                    let j = (y as usize) / block_size;
+            self.update_state(enemy, sees_player, sees_player && stare, dt);
 
-                    // Verificar que la nueva posición es válida
-                    if i < maze[0].len() && j < maze.len() && maze[j][i] == ' ' {
-                        enemy.position.x = x;
-                        enemy.position.y = y;
-                        break;
+            match enemy.state {
+                GhostState::Chase => {
+                    enemy.repath_timer -= dt;
+                    if enemy.repath_timer <= 0.0 || enemy.path.is_empty() {
+                        enemy.repath_timer = REPATH_SECS;
+                        self.set_path(enemy, maze, player_cell, block_size);
+                    }
+                }
+                GhostState::Flee => {
+                    if enemy.path.is_empty() {
+                        // Elegir, entre varias celdas al azar, la más lejana al jugador
+                        let target = (0..12)
+                            .filter_map(|_| random_cell_near(maze, enemy.position, 6.0, block_size, &mut rng))
+                            .max_by(|a, b| {
+                                let da = (cell_center(*a, block) - player.position).norm();
+                                let db = (cell_center(*b, block) - player.position).norm();
+                                da.total_cmp(&db)
+                            });
+                        if let Some(target) = target {
+                            self.set_path(enemy, maze, target, block_size);
+                        }
+                    }
+                }
+                GhostState::Wander => {
+                    if enemy.path.is_empty() {
+                        if let Some(target) = random_cell_near(maze, enemy.position, 5.0, block_size, &mut rng) {
+                            self.set_path(enemy, maze, target, block_size);
+                        }
+                    }
+                }
+                GhostState::Patrol => {
+                    if enemy.patrol_route.is_empty() {
+                        // La ruta empieza en la posición actual y sigue por celdas al azar
+                        let (i, j) = position_to_cell(enemy.position, block_size);
+                        enemy.patrol_route.push((i.max(0) as usize, j.max(0) as usize));
+                        for _ in 1..PATROL_POINTS {
+                            if let Some(cell) = random_cell_near(maze, enemy.position, 8.0, block_size, &mut rng) {
+                                enemy.patrol_route.push(cell);
+                            }
+                        }
+                    }
+                    if enemy.path.is_empty() {
+                        enemy.patrol_index = (enemy.patrol_index + 1) % enemy.patrol_route.len();
+                        let target = enemy.patrol_route[enemy.patrol_index];
+                        self.set_path(enemy, maze, target, block_size);
                     }
                 }
             }
+
+            // Persiguen y huyen a velocidad completa; al deambular o patrullar van más despacio
+            let speed = match enemy.state {
+                GhostState::Chase | GhostState::Flee => self.speed,
+                GhostState::Wander | GhostState::Patrol => self.speed * 0.6,
+            };
+            enemy.follow_path(speed * block, dt, block_size);
+        }
+
+        // Reaparecer cerca del jugador a los fantasmas que quedaron demasiado lejos
+        self.respawn_timer += dt;
+        if self.respawn_timer >= self.respawn_duration {
+            self.respawn_timer = 0.0; // Reiniciar el temporizador
+            self.respawn_far_ghosts(player.position, maze, enemies, block_size);
         }
     }
-}
\ No newline at end of file
+
+    // Transiciones entre estados según lo que el fantasma percibe
+    fn update_state(&self, enemy: &mut Enemy, sees_player: bool, stared_at: bool, dt: f32) {
+        let idle_state = match self.behavior {
+            GhostBehavior::Wander => GhostState::Wander,
+            GhostBehavior::Patrol => GhostState::Patrol,
+        };
+
+        match enemy.state {
+            GhostState::Flee => {
+                enemy.state_timer -= dt;
+                if enemy.state_timer <= 0.0 {
+                    enemy.state = idle_state;
+                    enemy.path.clear();
+                }
+            }
+            _ if stared_at => {
+                enemy.state = GhostState::Flee;
+                enemy.state_timer = FLEE_SECS;
+                enemy.path.clear();
+            }
+            _ if sees_player => {
+                enemy.state = GhostState::Chase;
+                enemy.state_timer = LOSE_SIGHT_SECS;
+            }
+            GhostState::Chase => {
+                enemy.state_timer -= dt;
+                if enemy.state_timer <= 0.0 {
+                    enemy.state = idle_state;
+                    enemy.path.clear();
+                }
+            }
+            state if state != idle_state => {
+                enemy.state = idle_state;
+                enemy.path.clear();
+            }
+            _ => {}
+        }
+    }
+
+    fn set_path(&self, enemy: &mut Enemy, maze: &[Vec<char>], target: Cell, block_size: usize) {
+        let (i, j) = position_to_cell(enemy.position, block_size);
+        let start = (i.max(0) as usize, j.max(0) as usize);
+        enemy.path = find_path(maze, start, target).unwrap_or_default().into();
+    }
+
+    fn respawn_far_ghosts(&self, player_position: Vec2, maze: &[Vec<char>], enemies: &mut [Enemy], block_size: usize) {
+        let mut rng = rand::thread_rng();
+        let min_distance = block_size as f32 * self.min_distance; // Distancia mínima al jugador
+        let max_distance = block_size as f32 * self.max_distance; // Distancia máxima al jugador
+
+        for enemy in enemies.iter_mut() {
+            let idle = matches!(enemy.state, GhostState::Wander | GhostState::Patrol);
+            if !idle || (enemy.position - player_position).norm() <= max_distance {
+                continue;
+            }
+
+            // Elegir una nueva posición cercana al jugador; se desiste tras varios intentos
+            for _ in 0..50 {
+                let distance = rng.gen_range(min_distance..max_distance);
+                let angle = rng.gen_range(0.0..(2.0 * std::f32::consts::PI));
+
+                // Calcular nuevas posiciones basadas en la distancia y el ángulo
+                let position = player_position + Vec2::new(angle.cos(), angle.sin()) * distance;
+                let (i, j) = position_to_cell(position, block_size);
+
+                // Verificar que la nueva posición es válida
+                if is_walkable(maze, i, j) {
+                    enemy.position = cell_center((i as usize, j as usize), block_size as f32);
+                    enemy.path.clear();
+                    enemy.patrol_route.clear();
+                    break;
+                }
+            }
+        }
+    }
+}
+
+fn cell_center((col, row): Cell, block: f32) -> Vec2 {
+    Vec2::new((col as f32 + 0.5) * block, (row as f32 + 0.5) * block)
+}
+
+// Celda transitable al azar dentro de un radio (en bloques) alrededor de una posición
+fn random_cell_near(
+    maze: &[Vec<char>],
+    position: Vec2,
+    radius: f32,
+    block_size: usize,
+    rng: &mut impl Rng,
+) -> Option<Cell> {
+    let (i, j) = position_to_cell(position, block_size);
+    let radius = radius as isize;
+
+    for _ in 0..20 {
+        let ci = i + rng.gen_range(-radius..=radius);
+        let cj = j + rng.gen_range(-radius..=radius);
+        if (ci, cj) != (i, j) && is_walkable(maze, ci, cj) {
+            return Some((ci as usize, cj as usize));
+        }
+    }
+    None
+}
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use crate::assets::{AssetKind, AssetManager};
+use crate::world::World;
+
+// Cada cuánto se revisan las fechas de modificación, en segundos
+const POLL_SECS: f32 = 0.5;
+
+// Archivo que cambió en disco
+pub enum Change {
+    Level,
+    Texture(String), // Nombre de la textura en la leyenda
+}
+
+// Vigila el archivo del nivel actual y sus texturas comparando fechas de
+// modificación, sin hilos ni servicios externos. Solo se usa en modo desarrollo.
+pub struct HotReload {
+    modified: HashMap<PathBuf, Option<SystemTime>>,
+    level: Option<PathBuf>,
+    textures: HashMap<PathBuf, String>,
+    elapsed: f32,
+}
+
+impl HotReload {
+    pub fn new() -> Self {
+        Self {
+            modified: HashMap::new(),
+            level: None,
+            textures: HashMap::new(),
+            elapsed: 0.0,
+        }
+    }
+
+    // Reemplaza los archivos vigilados por los del mundo actual; `level_name` es `None`
+    // para los niveles generados, que no tienen archivo
+    pub fn watch(&mut self, assets: &AssetManager, level_name: Option<&str>, world: &World) {
+        self.level = level_name.map(|name| assets.resolve(AssetKind::Level, name));
+        let level = &world.level;
+        self.textures = level
+            .legend
+            .values()
+            .chain(level.floor_legend.values())
+            .chain(level.ceiling_legend.values())
+            .map(|name| {
+                let kind = if assets.is_sheet(name) { AssetKind::Sheet } else { AssetKind::Texture };
+                (assets.resolve(kind, name), name.clone())
+            })
+            .collect();
+
+        self.modified = self
+            .level
+            .iter()
+            .chain(self.textures.keys())
+            .map(|path| (path.clone(), modified(path)))
+            .collect();
+    }
+
+    // Cambios detectados desde la última revisión; el disco se consulta cada POLL_SECS
+    pub fn poll(&mut self, dt: f32) -> Vec<Change> {
+        self.elapsed += dt;
+        if self.elapsed < POLL_SECS {
+            return Vec::new();
+        }
+        self.elapsed = 0.0;
+
+        let mut changes = Vec::new();
+        for (path, last) in self.modified.iter_mut() {
+            let current = modified(path);
+            if current == *last {
+                continue;
+            }
+            *last = current;
+
+            if self.level.as_ref() == Some(path) {
+                changes.push(Change::Level);
+            } else if let Some(name) = self.textures.get(path) {
+                changes.push(Change::Texture(name.clone()));
+            }
+        }
+        changes
+    }
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
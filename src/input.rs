@@ -0,0 +1,33 @@
+use std::collections::HashSet;
+use minifb::{Key, Window};
+
+// Estado del teclado por cuadro: recuerda las teclas del cuadro anterior para detectar
+// cuándo se presiona o se suelta una tecla, en lugar de solo si está presionada
+pub struct Input {
+    down: HashSet<Key>,
+    previous: HashSet<Key>,
+}
+
+impl Input {
+    pub fn new() -> Self {
+        Self {
+            down: HashSet::new(),
+            previous: HashSet::new(),
+        }
+    }
+
+    // Se llama una sola vez al inicio de cada cuadro
+    pub fn update(&mut self, window: &Window) {
+        self.previous = std::mem::take(&mut self.down);
+        self.down = window.get_keys().into_iter().collect();
+    }
+
+    pub fn is_down(&self, key: Key) -> bool {
+        self.down.contains(&key)
+    }
+
+    // La tecla se presionó en este cuadro
+    pub fn is_pressed(&self, key: Key) -> bool {
+        self.down.contains(&key) && !self.previous.contains(&key)
+    }
+}
@@ -0,0 +1,110 @@
+use nalgebra_glm::Vec2;
+use crate::maze::{is_walkable, Level};
+use crate::raycaster::has_line_of_sight;
+
+// Alcance de la linterna del jugador, en bloques
+const FLASHLIGHT_RANGE: f32 = 6.0;
+// Luz que suma la linterna en el centro del haz
+const FLASHLIGHT_INTENSITY: f32 = 1.2;
+// Radio del haz en pantalla, como fracción de la mitad de la altura de la vista
+const FLASHLIGHT_CONE: f32 = 0.6;
+
+// Luz (r, g, b) que recibe cada celda del laberinto; 1.0 deja la textura con su color original
+pub struct Lightmap {
+    width: usize,
+    height: usize,
+    cells: Vec<[f32; 3]>,
+}
+
+impl Lightmap {
+    // Suma la luz ambiente y la de cada fuente que ve la celda, con caída lineal hasta su radio
+    pub fn new(level: &Level) -> Self {
+        let grid = &level.grid;
+        let width = grid.first().map_or(0, |row| row.len());
+        let height = grid.len();
+        let block = level.block_size as f32;
+        let mut cells = vec![[level.ambient; 3]; width * height];
+
+        for light in &level.lights {
+            let source = level.cell_center(light.cell);
+            let color = [light.color.r, light.color.g, light.color.b].map(|c| c as f32 / 255.0);
+
+            for row in 0..height {
+                for col in 0..width {
+                    if !is_walkable(grid, col as isize, row as isize) {
+                        continue;
+                    }
+                    let target = level.cell_center((col, row));
+                    let distance = (target - source).norm() / block;
+                    if distance >= light.radius || !has_line_of_sight(grid, source, target, level.block_size) {
+                        continue;
+                    }
+
+                    let falloff = 1.0 - distance / light.radius;
+                    for (channel, amount) in cells[row * width + col].iter_mut().zip(color) {
+                        *channel += amount * falloff;
+                    }
+                }
+            }
+        }
+
+        // Las paredes toman la luz de la celda vecina más iluminada, para que las caras
+        // visibles brillen igual que el pasillo frente a ellas
+        let lit = cells.clone();
+        for row in 0..height {
+            for col in 0..width {
+                if is_walkable(grid, col as isize, row as isize) {
+                    continue;
+                }
+                let neighbors = [(0, -1), (0, 1), (-1, 0), (1, 0)]
+                    .iter()
+                    .map(|(dx, dy)| (col as isize + dx, row as isize + dy))
+                    .filter(|(i, j)| is_walkable(grid, *i, *j))
+                    .map(|(i, j)| lit[j as usize * width + i as usize]);
+                if let Some(brightest) = neighbors.max_by(|a, b| a.iter().sum::<f32>().total_cmp(&b.iter().sum())) {
+                    cells[row * width + col] = brightest;
+                }
+            }
+        }
+
+        Self { width, height, cells }
+    }
+
+    // Luz de una celda (columna, fila); las paredes tienen la del pasillo más iluminado frente a ellas
+    pub fn at(&self, (col, row): (usize, usize)) -> [f32; 3] {
+        if self.cells.is_empty() {
+            return [1.0; 3];
+        }
+        self.cells[row.min(self.height - 1) * self.width + col.min(self.width - 1)]
+    }
+
+    // Luz en una posición del mundo, interpolada entre los centros de las celdas vecinas
+    pub fn sample(&self, position: Vec2, block_size: usize) -> [f32; 3] {
+        if self.cells.is_empty() {
+            return [1.0; 3];
+        }
+        let block = block_size as f32;
+        let x = (position.x / block - 0.5).clamp(0.0, (self.width - 1) as f32);
+        let y = (position.y / block - 0.5).clamp(0.0, (self.height - 1) as f32);
+        let (col, row) = (x.floor() as usize, y.floor() as usize);
+        let (tx, ty) = (x - col as f32, y - row as f32);
+
+        let cell = |i: usize, j: usize| self.cells[j.min(self.height - 1) * self.width + i.min(self.width - 1)];
+        let (top_left, top_right) = (cell(col, row), cell(col + 1, row));
+        let (bottom_left, bottom_right) = (cell(col, row + 1), cell(col + 1, row + 1));
+
+        [0, 1, 2].map(|c| {
+            let top = top_left[c] + (top_right[c] - top_left[c]) * tx;
+            let bottom = bottom_left[c] + (bottom_right[c] - bottom_left[c]) * tx;
+            top + (bottom - top) * ty
+        })
+    }
+}
+
+// Luz que suma la linterna a un píxel. `screen_offset` es su distancia al centro de la vista
+// dividida por la mitad de la altura; `distance` es la del punto iluminado, en bloques.
+pub fn flashlight(screen_offset: f32, distance: f32) -> f32 {
+    let cone = (1.0 - screen_offset / FLASHLIGHT_CONE).clamp(0.0, 1.0);
+    let range = (1.0 - distance / FLASHLIGHT_RANGE).clamp(0.0, 1.0);
+    FLASHLIGHT_INTENSITY * cone * range
+}
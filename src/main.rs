@@ -1,264 +1,345 @@
 use minifb::{Key, Window, WindowOptions};
 use nalgebra_glm::Vec2;
-use rand::Rng;
-use rodio::{source::Source, Decoder, OutputStream, Sink};
+use rand::seq::SliceRandom;
+use rodio::{source::Source, Decoder, OutputStream, OutputStreamHandle, Sink};
 use std::f32::consts::PI;
-use std::fs::File;
-use std::io::BufReader;
+use std::io::Cursor;
 use std::time::{Duration, Instant};
 
+mod animation;
+mod assets;
+mod bindings;
+mod campaign;
 mod color;
 mod controller;
 mod enemy;
 mod fps;
 mod framebuffer;
+mod input;
 mod ghostmanager;
+mod hotreload;
+mod lighting;
 mod maze;
+mod mazegen;
 mod minimap;
+mod pathfinding;
 mod player;
 mod raycaster;
+mod sprites;
 mod texture;
+mod world;
 
+use crate::animation::{direction_index, SpriteSheet};
+use crate::assets::AssetManager;
+use crate::bindings::{Action, Bindings};
+use crate::campaign::{Campaign, LevelSource};
 use crate::color::Color;
-use crate::controller::process_events;
+use crate::controller::{collides, process_events, process_mouse_look};
 use crate::enemy::Enemy;
-use crate::framebuffer::Framebuffer;
+use crate::framebuffer::{BlendMode, Canvas, Framebuffer, FramebufferView, Viewport};
 use crate::ghostmanager::GhostManager;
-use crate::maze::load_maze;
+use crate::hotreload::{Change, HotReload};
+use crate::lighting::flashlight;
+use crate::input::Input;
+use crate::maze::{is_walkable, position_to_cell, Level};
+use crate::mazegen::GeneratorOptions;
 use crate::minimap::render_minimap;
 use crate::player::Player;
-use crate::raycaster::cast_ray;
+use crate::raycaster::{cast_ray, Side};
+use crate::sprites::{render_sprites, Projection, Sprite};
 use crate::texture::Texture;
-use fps::FPSCounter;
+use crate::world::World;
+use fps::{render_text, FPSCounter};
+
+// Distancia máxima que recorre un rayo antes de darse por perdido
+const MAX_RAY_DISTANCE: f32 = 2000.0;
+// Altura de los fantasmas en alturas de pared
+const GHOST_SIZE: f32 = 0.8;
+// Altura máxima a la que flotan los fantasmas, en alturas de pared
+const GHOST_FLOAT: f32 = 0.15;
+// Segundos de desfase entre las animaciones de fantasmas consecutivos
+const GHOST_PHASE: f32 = 0.37;
+
+// Paso fijo de la simulación en segundos; el movimiento avanza en pasos de este tamaño
+const FIXED_DT: f32 = 1.0 / 60.0;
+// Tiempo máximo de un cuadro que se simula, para no acumular una ráfaga de pasos tras una pausa larga
+const MAX_FRAME_TIME: f32 = 0.25;
 
 enum GameState {
     StartScreen,
     Playing,
+    Paused,
+    LevelComplete,
     Victory,
     Defeat,
 }
 
-fn render2d(
-    framebuffer: &mut Framebuffer,
-    player: &Player,
-    maze: &Vec<Vec<char>>,
-    textures: [&Texture; 3],
-    block_size: usize,
-) {
-    for row in 0..maze.len() {
-        for col in 0..maze[row].len() {
-            let xo = col * block_size;
-            let yo = row * block_size;
-
-            let texture = match maze[row][col] {
-                '+' => Some(&textures[0]),
-                '-' => Some(&textures[1]),
-                '|' => Some(&textures[2]),
-                _ => None,
-            };
+// Vistas disponibles mientras se juega
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ViewMode {
+    TopDown,    // Solo el mapa 2D
+    FirstPerson, // Solo la vista 3D con minimapa
+    Split,      // Mapa 2D y vista 3D lado a lado
+}
 
-            if let Some(texture) = texture {
-                for x in 0..block_size {
-                    for y in 0..block_size {
-                        let texture_x = (x * texture.width) / block_size;
-                        let texture_y = (y * texture.height) / block_size;
-                        let color = texture.get_pixel(texture_x, texture_y);
-                        framebuffer.set_current_color(color);
-                        framebuffer.point(xo + x, yo + y);
-                    }
-                }
+impl ViewMode {
+    fn next(self) -> Self {
+        match self {
+            ViewMode::TopDown => ViewMode::FirstPerson,
+            ViewMode::FirstPerson => ViewMode::Split,
+            ViewMode::Split => ViewMode::TopDown,
+        }
+    }
+
+    // Los fantasmas solo se mueven cuando la vista 3D está en pantalla
+    fn shows_3d(self) -> bool {
+        self != ViewMode::TopDown
+    }
+}
+
+fn render2d(framebuffer: &mut FramebufferView, player: &Player, world: &World) {
+    let maze = world.maze();
+    let block_size = world.block_size();
+
+    // Escalar el mapa para que quepa completo en la vista
+    let map_width = maze.iter().map(|row| row.len()).max().unwrap_or(1) * block_size;
+    let map_height = maze.len().max(1) * block_size;
+    let scale = (framebuffer.width() as f32 / map_width as f32).min(framebuffer.height() as f32 / map_height as f32);
+    let cell_size = (block_size as f32 * scale).ceil() as i32;
+
+    for (row, cells) in maze.iter().enumerate() {
+        for (col, cell) in cells.iter().enumerate() {
+            let xo = ((col * block_size) as f32 * scale) as i32;
+            let yo = ((row * block_size) as f32 * scale) as i32;
+
+            if let Some(texture) = world.textures.get(cell) {
+                framebuffer.blit(texture, xo, yo, cell_size, cell_size, None);
             } else {
                 framebuffer.set_current_color(Color::ground().to_hex());
-                for x in 0..block_size {
-                    for y in 0..block_size {
-                        framebuffer.point(xo + x, yo + y);
-                    }
-                }
+                framebuffer.fill_rect(xo, yo, cell_size, cell_size);
             }
         }
     }
     let num_rays = 5;
+    framebuffer.set_blend_mode(BlendMode::Additive);
     for i in 0..num_rays {
         let current_ray = i as f32 / num_rays as f32;
         let angle = player.a - (player.fov / 2.0) + (player.fov * current_ray);
-        cast_ray(framebuffer, &maze, &player, angle, block_size, true);
+        let intersect = cast_ray(maze, player.position, angle, block_size, MAX_RAY_DISTANCE);
+
+        // Dibujar el rayo hasta el punto de impacto; se suma al color de debajo como un haz de luz
+        framebuffer.set_current_rgba(0xC0000000 | Color::red().to_hex());
+        let start = player.position * scale;
+        let end = intersect.hit * scale;
+        framebuffer.line(start.x as i32, start.y as i32, end.x as i32, end.y as i32);
     }
+    framebuffer.set_blend_mode(BlendMode::Opaque);
 
+    // Marcar la salida con un anillo del tamaño de la zona de llegada
+    let exit_point = world.level.exit_position() * scale;
     framebuffer.set_current_color(Color::red().to_hex());
-    framebuffer.point(player.position.x as usize, player.position.y as usize);
+    framebuffer.circle(exit_point.x as i32, exit_point.y as i32, (10.0 * scale).max(2.0) as i32);
+
+    let player_point = player.position * scale;
+    framebuffer.fill_circle(player_point.x as i32, player_point.y as i32, (player.radius * scale).max(1.0) as i32);
 }
 
 fn render3d(
-    framebuffer: &mut Framebuffer,
+    framebuffer: &mut FramebufferView,
     player: &Player,
-    textures: [&Texture; 3],
-    ghost_texture: &Texture,
-    enemies: &Vec<Enemy>,
+    world: &World,
+    ghost_sheet: &SpriteSheet,
+    enemies: &[Enemy],
     scale_factor: usize,
 ) {
-    let maze = load_maze("./maze.txt");
-    let block_size = 50;
-    let num_rays = framebuffer.width;
-    let hh = framebuffer.height as f32 / 2.0;
+    let level = &world.level;
+    let maze = world.maze();
+    let block_size = world.block_size();
+    let num_rays = framebuffer.width();
+    let hh = framebuffer.height() as f32 / 2.0;
 
     for y in 0..hh as usize {
         let ratio = y as f32 / hh;
-        let sky_color = Color::gradient_sky(ratio).to_hex();
+        let sky_color = Color::gradient(level.sky_top, level.sky_bottom, ratio).to_hex();
         framebuffer.set_current_color(sky_color);
-        for x in 0..framebuffer.width {
-            framebuffer.point(x, y);
-        }
+        framebuffer.hspan(0, framebuffer.width() as i32 - 1, y as i32);
     }
 
-    framebuffer.set_current_color(Color::ground().to_hex());
-    for y in hh as usize..framebuffer.height {
-        for x in 0..framebuffer.width {
-            framebuffer.point(x, y);
-        }
+    let block = block_size as f32;
+    let distance_to_projection_plane = 40.0;
+    let fog_color = level.sky_bottom; // La niebla se funde con el horizonte
+
+    // Distancia sobre el plano del suelo (o del techo) que se ve en cada fila de pantalla:
+    // la misma que tendría un poste con base (o tope) en esa fila
+    let row_distance = |y: usize| hh * (distance_to_projection_plane / 2.0) / (y as f32 + 0.5 - hh).abs();
+
+    for y in hh as usize..framebuffer.height() {
+        let fog = level.fog.amount(row_distance(y) / block);
+        framebuffer.set_current_color(Color::gradient(level.ground, fog_color, fog).to_hex());
+        framebuffer.hspan(0, framebuffer.width() as i32 - 1, y as i32);
     }
 
+    // Distancia en píxeles a la pantalla que da el campo de visión del jugador
+    let focal_length = (framebuffer.width() as f32 / 2.0) / (player.fov / 2.0).tan();
+
+    // Luz de un punto del mundo visto en el píxel (x, y): la del mapa de luz del nivel más
+    // la de la linterna, que ilumina el centro de la pantalla y se apaga con la distancia
+    let center_x = framebuffer.width() as f32 / 2.0;
+    let add_flashlight = |light: [f32; 3], x: usize, y: usize, distance: f32| {
+        if !player.flashlight {
+            return light;
+        }
+        let screen_offset = (x as f32 - center_x).hypot(y as f32 - hh) / hh;
+        let beam = flashlight(screen_offset, distance / block);
+        light.map(|channel| channel + beam)
+    };
+    let light_at = |point: Vec2, x: usize, y: usize, distance: f32| {
+        add_flashlight(world.lightmap.sample(point, block_size), x, y, distance)
+    };
+
+    // Distancia perpendicular a la pared de cada columna, para recortar los sprites
+    let mut depth = vec![f32::INFINITY; num_rays];
+
     // Renderizado de las paredes con texturas escaladas
-    for i in 0..num_rays {
+    for (i, column_depth) in depth.iter_mut().enumerate() {
         let current_ray = i as f32 / num_rays as f32;
         let a = player.a - (player.fov / 2.0) + (player.fov * current_ray);
-        let intersect = cast_ray(framebuffer, &maze, &player, a, block_size, false);
+        let intersect = cast_ray(maze, player.position, a, block_size, MAX_RAY_DISTANCE);
 
         let distance_to_wall = intersect.distance * (a - player.a).cos();
-        let distance_to_projection_plane = 40.0;
 
         let stake_height = (hh / distance_to_wall) * distance_to_projection_plane;
         let stake_top = (hh - (stake_height / 2.0)) as usize;
-        let stake_bottom = (hh + (stake_height / 2.0)) as usize;
-
-        let texture = match intersect.impact {
-            '+' => &textures[0],
-            '-' => &textures[1],
-            '|' => &textures[2],
-            _ => continue,
-        };
-
-        let texture_x = (intersect.distance % (texture.width / scale_factor) as f32) as usize;
+        let stake_bottom = ((hh + (stake_height / 2.0)) as usize).min(framebuffer.height());
+
+        // Suelo y techo texturizados a lo largo del rayo
+        let ray_direction = Vec2::new(a.cos(), a.sin());
+        let floor_rows = stake_bottom..framebuffer.height();
+        let ceiling_rows = 0..stake_top.min(hh as usize);
+        for y in floor_rows.chain(ceiling_rows) {
+            let is_floor = y as f32 >= hh;
+            let row_distance = row_distance(y);
+            let point = player.position + ray_direction * (row_distance / (a - player.a).cos());
+            let (col, row) = position_to_cell(point, block_size);
+
+            let textures = if is_floor { &world.floor_textures } else { &world.ceiling_textures };
+            let Some(texture) = textures.get(&level.floor_glyph(col, row)) else {
+                continue; // Sin textura queda el color liso del suelo o el degradado del cielo
+            };
 
-        for y in stake_top..stake_bottom {
-            let texture_y = ((y - stake_top) * (texture.height / scale_factor)) / (stake_bottom - stake_top);
-            let color = texture.get_pixel(texture_x, texture_y);
-            framebuffer.set_current_color(color);
+            let u = point.x / block - col as f32;
+            let v = point.y / block - row as f32;
+            let texels_per_pixel = texture.width as f32 * row_distance / (block * focal_length);
+            let color = texture.sample(u, v, level.wall_sampler, texture.mip_level_for(texels_per_pixel));
+            let color = Color::modulate_hex(color, light_at(point, i, y, row_distance));
+            let fog = level.fog.amount(row_distance / block);
+            framebuffer.set_current_color(Color::mix_hex(color, fog_color, fog));
             framebuffer.point(i, y);
         }
-    }
 
-    // Límite máximo de distancia para renderizar fantasmas
-    let max_render_distance = 250.0;
-    for enemy in enemies.iter() {
-        let dx = enemy.position.x - player.position.x;
-        let dy = enemy.position.y - player.position.y;
-        let distance_to_enemy = (dx * dx + dy * dy).sqrt();
-
-        // No renderizar fantasmas que estén más lejos que el límite
-        if distance_to_enemy > max_render_distance {
+        if !world.textures.contains_key(&intersect.impact) {
             continue;
         }
+        *column_depth = distance_to_wall;
 
-        if distance_to_enemy > 0.5 {
-            let enemy_angle = (dy).atan2(dx);
-            let relative_angle = enemy_angle - player.a;
+        // Reflejar la coordenada en las caras opuestas para que la textura se lea igual desde ambos lados
+        let offset = match intersect.side {
+            Side::Vertical if a.cos() < 0.0 => 1.0 - intersect.offset,
+            Side::Horizontal if a.sin() > 0.0 => 1.0 - intersect.offset,
+            _ => intersect.offset,
+        };
 
-            // Verificar si el fantasma está en el campo de visión
-            if relative_angle.abs() < player.fov / 2.0 {
-                let intersect =
-                    cast_ray(framebuffer, &maze, &player, enemy_angle, block_size, false);
+        // Las paredes animadas se leen del cuadro actual de su hoja en lugar del atlas
+        let frame = world.wall_sheets.get(&intersect.impact).map(|sheet| sheet.frame(world.time, 0));
 
-                if intersect.distance < distance_to_enemy {
-                    continue;
-                }
-                let distance_to_projection_plane = 80.0;
-                let enemy_height = (hh / distance_to_enemy) * distance_to_projection_plane;
-                let enemy_top = (hh - (enemy_height / 2.0)) as usize;
-                let enemy_bottom = (hh + (enemy_height / 2.0)) as usize;
-
-                let enemy_screen_position = (framebuffer.width as f32 / 2.0)
-                    + (relative_angle / player.fov) * framebuffer.width as f32;
-                let enemy_screen_position = enemy_screen_position as usize;
-
-                let ghost_width = ghost_texture.width;
-                let ghost_height = ghost_texture.height;
-
-                for y in enemy_top..enemy_bottom {
-                    let texture_y = ((y - enemy_top) * ghost_height) / (enemy_bottom - enemy_top);
-                    let mut x_offset = 0;
-
-                    for x in enemy_screen_position..(enemy_screen_position + ghost_width) {
-                        if x >= framebuffer.width {
-                            continue;
-                        }
-
-                        let texture_x = (x_offset * ghost_texture.width) / ghost_width;
-                        let color = ghost_texture.get_pixel(texture_x, texture_y);
-
-                        //Renderizar el píxel solo si no es transparente
-                        let r = ((color >> 24) & 0xFF) as u8;
-                        let g = ((color >> 16) & 0xFF) as u8;
-                        let b = ((color >> 8) & 0xFF) as u8;
-                        let a = (color & 0xFF) as u8;
-
-                        // Solo renderizar el píxel si no es completamente transparente
-                        if a > 0 {
-                            framebuffer.set_current_color(Color::new(r, g, b).to_hex());
-                            framebuffer.point(x, y);
-                        }
-                        x_offset += 1;
-                    }
-                }
-            }
+        // Cada pared muestra solo una fracción de la textura, ampliada `scale_factor` veces;
+        // los cuadros de una animación se muestran completos
+        let sample_span = if frame.is_some() { 1.0 } else { 1.0 / scale_factor as f32 };
+        let u = offset * sample_span;
+
+        // Elegir el mipmap según cuántos texels caen en cada píxel del poste
+        let tile_size = frame.map_or(world.atlas.tile_size, |frame| frame.width);
+        let texels_per_pixel = tile_size as f32 * sample_span / stake_height;
+        let mip_level = match frame {
+            Some(frame) => frame.mip_level_for(texels_per_pixel),
+            None => world.atlas.mip_level_for(texels_per_pixel),
+        };
+
+        // Las caras norte y sur se ven más oscuras que las este y oeste para distinguir las esquinas
+        let side_shade = match intersect.side {
+            Side::Horizontal => level.side_shade,
+            Side::Vertical => 1.0,
+        };
+        let fog = level.fog.amount(distance_to_wall / block);
+
+        // La pared recibe la luz de su celda en el mapa de luz
+        let wall_light = intersect.cell.map_or([level.ambient; 3], |cell| world.lightmap.at(cell));
+
+        for y in stake_top..stake_bottom {
+            // Calcular la fila de la textura respecto al poste completo, aunque esté recortado por la pantalla
+            let wall_y = (y as f32 - (hh - stake_height / 2.0)) / stake_height;
+            let v = wall_y * sample_span;
+            let color = match frame {
+                Some(frame) => frame.sample(u, v, level.wall_sampler, mip_level),
+                None => world.atlas.sample(intersect.impact, u, v, level.wall_sampler, mip_level).unwrap_or(0),
+            };
+            let color = Color::modulate_hex(color, add_flashlight(wall_light, i, y, distance_to_wall));
+            let color = Color::mix_hex(color, Color::black(), 1.0 - side_shade);
+            framebuffer.set_current_color(Color::mix_hex(color, fog_color, fog));
+            framebuffer.point(i, y);
         }
     }
+
+    // Los fantasmas se pierden en la niebla y reciben la misma luz que las paredes a su distancia
+    // Cada fantasma flota, parpadea y se muestra girado según hacia dónde avanza; el desfase
+    // evita que todos se muevan al mismo tiempo
+    let mut sprites: Vec<Sprite> = enemies
+        .iter()
+        .enumerate()
+        .map(|(index, enemy)| {
+            let time = world.time + index as f32 * GHOST_PHASE;
+            let to_player = player.position - enemy.position;
+            let direction = direction_index(enemy.heading, to_player.y.atan2(to_player.x));
+            Sprite {
+                position: enemy.position,
+                texture: ghost_sheet.frame(time, direction),
+                size: GHOST_SIZE,
+                elevation: GHOST_FLOAT * (1.0 + (time * 2.0).sin()) / 2.0,
+                opacity: 0.85 + 0.15 * (time * 9.0).sin(),
+            }
+        })
+        .collect();
+    let projection = Projection { horizon: hh, wall_height: hh * distance_to_projection_plane };
+    render_sprites(framebuffer, player, &mut sprites, &depth, &projection, |color, position, x, y, distance| {
+        let color = Color::modulate_hex(color, light_at(position, x, y, distance));
+        Color::mix_hex(color, fog_color, level.fog.amount(distance / block))
+    });
 }
 
 //Renderizar pantalla de inicio
-fn render_start_screen(framebuffer: &mut Framebuffer, start_texture: &Texture) {
-    for y in 0..framebuffer.height {
-        for x in 0..framebuffer.width {
-            let texture_x = (x * start_texture.width) / framebuffer.width;
-            let texture_y = (y * start_texture.height) / framebuffer.height;
-            let color = start_texture.get_pixel(texture_x, texture_y);
-            framebuffer.set_current_color(color);
-            framebuffer.point(x, y);
-        }
-    }
+fn render_start_screen(framebuffer: &mut FramebufferView, start_texture: &Texture) {
+    let (width, height) = (framebuffer.width() as i32, framebuffer.height() as i32);
+    framebuffer.blit(start_texture, 0, 0, width, height, None);
 }
 
 //Renderizar pantalla de éxito
-fn render_victory_screen(framebuffer: &mut Framebuffer, victory_texture: &Texture) {
-    for y in 0..framebuffer.height {
-        for x in 0..framebuffer.width {
-            let texture_x = (x * victory_texture.width) / framebuffer.width;
-            let texture_y = (y * victory_texture.height) / framebuffer.height;
-            let color = victory_texture.get_pixel(texture_x, texture_y);
-            framebuffer.set_current_color(color);
-            framebuffer.point(x, y);
-        }
-    }
+fn render_victory_screen(framebuffer: &mut FramebufferView, victory_texture: &Texture) {
+    let (width, height) = (framebuffer.width() as i32, framebuffer.height() as i32);
+    framebuffer.blit(victory_texture, 0, 0, width, height, None);
 }
 
 //Renderizar pantalla de derrota
-fn render_defeat_screen(framebuffer: &mut Framebuffer, defeat_texture: &Texture) {
-    for y in 0..framebuffer.height {
-        for x in 0..framebuffer.width {
-            let texture_x = (x * defeat_texture.width) / framebuffer.width;
-            let texture_y = (y * defeat_texture.height) / framebuffer.height;
-            let color = defeat_texture.get_pixel(texture_x, texture_y);
-            framebuffer.set_current_color(color);
-            framebuffer.point(x, y);
-        }
-    }
+fn render_defeat_screen(framebuffer: &mut FramebufferView, defeat_texture: &Texture) {
+    let (width, height) = (framebuffer.width() as i32, framebuffer.height() as i32);
+    framebuffer.blit(defeat_texture, 0, 0, width, height, None);
 }
 
-fn player_reached_end(player_position: &Vec2) -> bool {
-    let end_position = Vec2::new(524.0, 573.0); // Coordenadas del final
+fn player_reached_end(level: &Level, player_position: &Vec2) -> bool {
+    let end_position = level.exit_position(); // Coordenadas del final
     (player_position - end_position).norm() < 10.0 // Si está cerca del final
 }
 
-fn ghost_touched_player(enemies: &Vec<Enemy>, player_position: &Vec2) -> bool {
+fn ghost_touched_player(enemies: &[Enemy], player_position: &Vec2) -> bool {
     for enemy in enemies {
         if enemy.check_collision_with_player(player_position, 10.0) {
             return true;
@@ -266,17 +347,189 @@ fn ghost_touched_player(enemies: &Vec<Enemy>, player_position: &Vec2) -> bool {
     }
     false
 }
-fn main() {
-    let (_stream, stream_handle) =
-        OutputStream::try_default().expect("No se pudo inicializar el stream de audio.");
-    let sink = Sink::try_new(&stream_handle).expect("No se pudo crear el sink de audio.");
+// Opciones de la línea de comandos
+struct Arguments {
+    generator: Option<GeneratorOptions>, // Sin `--seed` se juega la campaña
+    dev_mode: bool,                      // `--dev` recarga el nivel y sus texturas al modificarlos
+}
+
+fn parse_arguments() -> Result<Arguments, String> {
+    let mut args = std::env::args().skip(1);
+    let mut options: Option<GeneratorOptions> = None;
+    let mut dev_mode = false;
+    let mut algorithm = None;
+    let mut size = None;
+    let mut braid = None;
+    let mut rooms = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("falta el valor de {}", arg));
+        match arg.as_str() {
+            "--seed" => {
+                let seed = value()?.parse().map_err(|_| "--seed debe ser un número".to_string())?;
+                options = Some(GeneratorOptions::new(seed));
+            }
+            "--algorithm" => algorithm = Some(value()?.parse()?),
+            "--size" => {
+                let value = value()?;
+                let (width, height) = value
+                    .split_once('x')
+                    .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+                    .ok_or_else(|| "--size debe tener la forma ANCHOxALTO".to_string())?;
+                size = Some((width, height));
+            }
+            "--braid" => braid = Some(value()?.parse().map_err(|_| "--braid debe ser un número entre 0 y 1".to_string())?),
+            "--rooms" => rooms = Some(value()?.parse().map_err(|_| "--rooms debe ser un número".to_string())?),
+            "--dev" => dev_mode = true,
+            _ => return Err(format!("argumento desconocido: {}", arg)),
+        }
+    }
+
+    if let Some(options) = &mut options {
+        if let Some(algorithm) = algorithm {
+            options.algorithm = algorithm;
+        }
+        if let Some((width, height)) = size {
+            options.width = width;
+            options.height = height;
+        }
+        if let Some(braid) = braid {
+            options.braid = braid;
+        }
+        if let Some(rooms) = rooms {
+            options.rooms = rooms;
+        }
+    }
+
+    Ok(Arguments { generator: options, dev_mode })
+}
+
+// Carga el nivel actual de la campaña o termina el programa con un mensaje descriptivo
+fn load_level(campaign: &Campaign, assets: &AssetManager) -> Level {
+    campaign.load_current(assets).unwrap_or_else(|err| {
+        eprintln!("Error al cargar {}: {}", campaign.current_name(), err);
+        std::process::exit(1);
+    })
+}
+
+// Crear enemigos en las posiciones marcadas por el nivel o en posiciones válidas aleatorias
+fn spawn_enemies(level: &Level) -> Vec<Enemy> {
+    let maze = &level.grid;
+    let block_size = level.block_size;
+    let player_start_position = level.spawn_position();
+    let mut rng = rand::thread_rng();
+    let mut enemies = vec![];
 
-    let file = File::open("assets/epiphanyts.wav").expect("No se pudo abrir el archivo de música.");
-    let source =
-        Decoder::new(BufReader::new(file)).expect("No se pudo decodificar el archivo de música.");
+    if level.ghost_spawns.is_empty() {
+        // Celdas libres a más de un bloque de la salida del jugador; si no hay ninguna,
+        // el nivel empieza sin fantasmas en lugar de buscar para siempre
+        let cells: Vec<Vec2> = maze
+            .iter()
+            .enumerate()
+            .flat_map(|(j, row)| (0..row.len()).map(move |i| (i, j)))
+            .filter(|(i, j)| is_walkable(maze, *i as isize, *j as isize))
+            .map(|cell| level.cell_center(cell))
+            .filter(|position| (position - player_start_position).norm() > block_size as f32)
+            .collect();
+
+        for _ in 0..level.ghosts.count {
+            if let Some(position) = cells.choose(&mut rng) {
+                enemies.push(Enemy::new(position.x, position.y));
+            }
+        }
+    } else {
+        for cell in &level.ghost_spawns {
+            let position = level.cell_center(*cell);
+            enemies.push(Enemy::new(position.x, position.y));
+        }
+    }
+
+    enemies
+}
+
+// Reproduce la música del nivel en un sink nuevo; al soltar el anterior se detiene su pista.
+// Si la pista no se puede cargar, el nivel se juega en silencio.
+fn play_music(stream_handle: &OutputStreamHandle, assets: &mut AssetManager, music: &str) -> Option<Sink> {
+    let source = assets
+        .sound(music)
+        .map_err(|err| err.to_string())
+        .and_then(|sound| Decoder::new(Cursor::new(sound)).map_err(|err| err.to_string()));
+    let source = match source {
+        Ok(source) => source,
+        Err(err) => {
+            eprintln!("No se pudo cargar la música {}: {}", music, err);
+            return None;
+        }
+    };
 
+    let sink = Sink::try_new(stream_handle).expect("No se pudo crear el sink de audio.");
     sink.append(source.repeat_infinite());
     sink.play();
+    Some(sink)
+}
+
+// Aplica los cambios detectados en modo desarrollo. Un nivel con errores se informa y se
+// ignora, para seguir jugando el anterior mientras se corrige el archivo.
+fn apply_hot_reload(
+    changes: Vec<Change>,
+    assets: &mut AssetManager,
+    campaign: &Campaign,
+    world: &mut World,
+    player: &mut Player,
+    enemies: &mut Vec<Enemy>,
+    ghost_manager: &mut GhostManager,
+) {
+    for change in changes {
+        match change {
+            Change::Texture(name) => {
+                assets.invalidate_texture(&name);
+                world.refresh_textures(assets);
+                println!("Textura recargada: {}", name);
+            }
+            Change::Level => match campaign.load_current(assets) {
+                Ok(level) => {
+                    *world = World::new(level, assets);
+                    player.radius = world.level.player_radius();
+
+                    // Conservar la posición del jugador mientras siga siendo válida en el nuevo laberinto
+                    if collides(world.maze(), world.block_size(), player.radius, player.position) {
+                        player.position = world.level.spawn_position();
+                        player.a = world.level.facing;
+                    }
+                    *enemies = spawn_enemies(&world.level);
+                    *ghost_manager = GhostManager::new(&world.level.ghosts);
+                    println!("Nivel recargado: {}", campaign.current_name());
+                }
+                Err(err) => eprintln!("No se pudo recargar {}: {}", campaign.current_name(), err),
+            },
+        }
+    }
+}
+
+fn main() {
+    let arguments = parse_arguments().unwrap_or_else(|err| {
+        eprintln!("Error en los argumentos: {}", err);
+        std::process::exit(1);
+    });
+
+    // Con `--seed` se juega un único nivel generado; si no, la lista de niveles de campaign.txt
+    let mut campaign = match arguments.generator {
+        Some(options) => Campaign::new(vec![LevelSource::Generated(options)]),
+        None => Campaign::from_file("./campaign.txt")
+            .unwrap_or_else(|_| Campaign::new(vec![LevelSource::File("./maze.txt".to_string())])),
+    };
+
+    // La campaña y la leyenda por defecto usan nombres del manifiesto, así que sin él no se puede jugar
+    let mut assets = AssetManager::from_manifest("assets").unwrap_or_else(|err| {
+        eprintln!("No se pudo leer el manifiesto de recursos ({}); el juego necesita assets/manifest.txt", err);
+        std::process::exit(1);
+    });
+    let mut world = World::new(load_level(&campaign, &assets), &mut assets);
+
+    let (_stream, stream_handle) =
+        OutputStream::try_default().expect("No se pudo inicializar el stream de audio.");
+    let mut current_music = world.level.music.clone();
+    let mut _sink = current_music.as_deref().and_then(|music| play_music(&stream_handle, &mut assets, music));
 
     let window_width = 50 * 19;
     let window_height = 50 * 13;
@@ -285,6 +538,17 @@ fn main() {
     let mut close_delay = Duration::from_millis(16);
 
     let mut framebuffer = Framebuffer::new(framebuffer_width, framebuffer_height);
+    let screen = framebuffer.viewport();
+
+    // Controles configurables; sin controls.cfg se usan los de siempre
+    let mut bindings = if std::path::Path::new("./controls.cfg").exists() {
+        Bindings::from_file("./controls.cfg").unwrap_or_else(|err| {
+            eprintln!("Error al cargar los controles: {}", err);
+            std::process::exit(1);
+        })
+    } else {
+        Bindings::new()
+    };
 
     let mut window = Window::new(
         "Whispers of Epiphany",
@@ -297,123 +561,210 @@ fn main() {
     framebuffer.set_background_color(Color::white().to_hex());
 
     let mut player = Player {
-        position: Vec2::new(100.0, 200.0),
-        a: PI / 3.0,
+        position: world.level.spawn_position(),
+        a: world.level.facing,
         fov: PI / 3.0,
+        radius: world.level.player_radius(),
+        flashlight: false,
     };
 
-    let maze = load_maze("./maze.txt");
-    let block_size = 50;
+    // Minimapa en la esquina superior derecha de la vista 3D
     let minimap_size = 200;
-    let wall_texture1 = Texture::from_file("assets/texture1.jpg");
-    let wall_texture2 = Texture::from_file("assets/texture3.jpg");
-    let wall_texture3 = Texture::from_file("assets/texture2.jpg");
-    let ghost_texture = Texture::from_file("assets/ghost.png");
+    let minimap = Viewport::new(screen.width - minimap_size - 50, 20, minimap_size, minimap_size);
+    let ghost_sheet = assets.sheet_or_still("ghost");
 
-    let start_texture = Texture::from_file("assets/woe.jpg");
-    let victory_texture = Texture::from_file("assets/won.jpg");
-    let defeat_texture = Texture::from_file("assets/failed.jpg");
+    let start_texture = assets.texture_or_missing("start_screen");
+    let victory_texture = assets.texture_or_missing("victory_screen");
+    let defeat_texture = assets.texture_or_missing("defeat_screen");
 
-    let textures = [&wall_texture1, &wall_texture2, &wall_texture3];
+    let mut enemies = spawn_enemies(&world.level);
 
-    // Crear enemigos en posiciones válidas cerca del jugador
-    let mut rng = rand::thread_rng();
-    let mut enemies = vec![];
-    let player_start_position = Vec2::new(100.0, 200.0);
-
-    for _ in 0..5 {
-        loop {
-            let x = rng.gen_range(1..maze[0].len()) as f32 * block_size as f32;
-            let y = rng.gen_range(1..maze.len()) as f32 * block_size as f32;
-
-            let i = (x as usize) / block_size;
-            let j = (y as usize) / block_size;
-
-            if maze[j][i] == ' '
-                && (Vec2::new(x, y) - player_start_position).norm() > block_size as f32
-            {
-                enemies.push(Enemy::new(x, y));
-                break;
-            }
-        }
-    }
-
-    let mut ghost_manager = GhostManager::new();
+    let mut ghost_manager = GhostManager::new(&world.level.ghosts);
     let mut fps_counter = FPSCounter::new();
 
+    // En modo desarrollo se vigilan el archivo del nivel y sus texturas
+    let mut hot_reload = arguments.dev_mode.then(|| {
+        let mut hot_reload = HotReload::new();
+        hot_reload.watch(&assets, campaign.current_file(), &world);
+        hot_reload
+    });
+
+    let mut input = Input::new();
     let mut game_state = GameState::StartScreen;
-    let mut mode = "2D"; // Modo inicial
+    let mut view_mode = ViewMode::TopDown; // Modo inicial
+    let mut level_timer = Instant::now();
+    let mut last_frame = Instant::now();
+    let mut accumulator = 0.0;
 
     while window.is_open() && !window.is_key_down(Key::Escape) {
         let start_time = Instant::now();
+        let frame_dt = (start_time - last_frame).as_secs_f32().min(MAX_FRAME_TIME);
+        last_frame = start_time;
+
+        // Las transiciones de estado usan flancos de tecla para que una sola pulsación
+        // no se repita entre cuadros ni entre pantallas
+        input.update(&window);
 
         fps_counter.update();
         match game_state {
             GameState::StartScreen => {
-                render_start_screen(&mut framebuffer, &start_texture);
-                if window.is_key_down(Key::Enter) {
+                render_start_screen(&mut framebuffer.view(screen), &start_texture);
+                if input.is_pressed(Key::Enter) {
                     game_state = GameState::Playing;
-                    player.position = player_start_position;
+                    // Reiniciar el nivel: jugador, fantasmas y animaciones vuelven al estado inicial
+                    player.position = world.level.spawn_position();
+                    player.a = world.level.facing;
+                    enemies = spawn_enemies(&world.level);
+                    ghost_manager = GhostManager::new(&world.level.ghosts);
+                    world.time = 0.0;
+                    level_timer = Instant::now();
                 }
             }
             GameState::Playing => {
-                // Cambiar entre los modos 2D y 3D
-                if window.is_key_down(Key::M) {
-                    mode = if mode == "2D" { "3D" } else { "2D" };
+                if let Some(hot_reload) = &mut hot_reload {
+                    let changes = hot_reload.poll(frame_dt);
+                    if !changes.is_empty() {
+                        apply_hot_reload(
+                            changes,
+                            &mut assets,
+                            &campaign,
+                            &mut world,
+                            &mut player,
+                            &mut enemies,
+                            &mut ghost_manager,
+                        );
+                        hot_reload.watch(&assets, campaign.current_file(), &world);
+                    }
+                }
+
+                world.time += frame_dt;
+                let maze = world.maze();
+                let block_size = world.block_size();
+
+                // Alternar entre mapa 2D, vista 3D y pantalla dividida
+                if bindings.is_pressed(&input, Action::ToggleView) {
+                    view_mode = view_mode.next();
+                }
+                if bindings.is_pressed(&input, Action::Pause) {
+                    game_state = GameState::Paused;
+                }
+                if bindings.is_pressed(&input, Action::Flashlight) {
+                    player.flashlight = !player.flashlight;
+                }
+
+                process_mouse_look(&window, &mut bindings, &mut player);
+
+                // Simular en pasos fijos el tiempo transcurrido desde el cuadro anterior
+                accumulator += frame_dt;
+                while accumulator >= FIXED_DT {
+                    process_events(&input, &bindings, &mut player, maze, block_size, FIXED_DT);
+                    if view_mode.shows_3d() {
+                        ghost_manager.update_ghosts(&player, maze, &mut enemies, block_size, FIXED_DT);
+                    }
+                    accumulator -= FIXED_DT;
                 }
 
-                process_events(&mut window, &mut player, &maze, block_size);
                 framebuffer.clear();
 
-                // Lógica de renderizado para 2D o 3D
-                if mode == "2D" {
-                    render2d(&mut framebuffer, &player, &maze, textures, block_size);
-                } else {
-                    render3d(
-                        &mut framebuffer,
-                        &player,
-                        textures,
-                        &ghost_texture,
-                        &enemies,
-                        5
-                    );
-                    render_minimap(
-                        &mut framebuffer,
-                        &player,
-                        &maze,
-                        minimap_size,
-                        block_size,
-                        textures
-                    );
-                    ghost_manager.update_ghosts(player.position, &maze, &mut enemies, block_size);
-                    
+                // Lógica de renderizado según el modo de vista
+                match view_mode {
+                    ViewMode::TopDown => {
+                        render2d(&mut framebuffer.view(screen), &player, &world);
+                    }
+                    ViewMode::FirstPerson => {
+                        let mut view = framebuffer.view(screen);
+                        render3d(
+                            &mut view,
+                            &player,
+                            &world,
+                            &ghost_sheet,
+                            &enemies,
+                            5
+                        );
+                        render_minimap(&mut view.view(minimap), &player, &world);
+                    }
+                    ViewMode::Split => {
+                        let (left, right) = screen.split_horizontal();
+                        render2d(&mut framebuffer.view(left), &player, &world);
+                        render3d(
+                            &mut framebuffer.view(right),
+                            &player,
+                            &world,
+                            &ghost_sheet,
+                            &enemies,
+                            5
+                        );
+                    }
                 }
-                fps_counter.render(&mut framebuffer, 10, 10, 2);
+                fps_counter.render(&mut framebuffer.view(screen), 10, 10, 2);
 
                 // Verificar si el jugador ha ganado o perdido
-                if player_reached_end(&player.position) {
-                    game_state = GameState::Victory;
-                } else if ghost_touched_player(&enemies, &player.position) {
+                let caught = ghost_touched_player(&enemies, &player.position)
+                    || enemies.iter().any(|enemy| {
+                        enemy.check_collision_with_player(&player.position, block_size as f32 / 2.0)
+                    });
+
+                if player_reached_end(&world.level, &player.position) {
+                    game_state = if campaign.complete_level(level_timer.elapsed()) {
+                        GameState::LevelComplete
+                    } else {
+                        GameState::Victory
+                    };
+                } else if caught {
+                    campaign.record_death(level_timer.elapsed());
                     game_state = GameState::Defeat;
                 }
-
-                // Renderizado y lógica de los fantasmas
-                for enemy in &mut enemies {
-                    if enemy.check_collision_with_player(&player.position, block_size as f32 / 2.0)
-                    {
-                        game_state = GameState::Defeat;
+            }
+            GameState::Paused => {
+                // El último cuadro queda congelado en pantalla hasta reanudar; se sigue el cursor
+                // para que el giro con el ratón no salte al volver
+                bindings.mouse_turn(&window);
+                if bindings.is_pressed(&input, Action::Pause) {
+                    game_state = GameState::Playing;
+                }
+            }
+            GameState::LevelComplete => {
+                // Mostrar el progreso de la campaña sobre la pantalla de éxito
+                render_victory_screen(&mut framebuffer.view(screen), &victory_texture);
+                let progress = format!("{}/{}", campaign.stats.levels_completed, campaign.level_count());
+                render_text(&mut framebuffer.view(screen), &progress, 10, 10, 4);
+
+                if input.is_pressed(Key::Enter) {
+                    // Cargar el siguiente nivel y conservar las estadísticas de la campaña
+                    world = World::new(load_level(&campaign, &assets), &mut assets);
+                    if let Some(hot_reload) = &mut hot_reload {
+                        hot_reload.watch(&assets, campaign.current_file(), &world);
                     }
+                    enemies = spawn_enemies(&world.level);
+                    ghost_manager = GhostManager::new(&world.level.ghosts);
+                    if world.level.music != current_music {
+                        current_music = world.level.music.clone();
+                        _sink = current_music.as_deref().and_then(|music| play_music(&stream_handle, &mut assets, music));
+                    }
+
+                    player.position = world.level.spawn_position();
+                    player.a = world.level.facing;
+                    player.radius = world.level.player_radius();
+                    level_timer = Instant::now();
+                    game_state = GameState::Playing;
                 }
             }
             GameState::Victory => {
-                render_victory_screen(&mut framebuffer, &victory_texture);
-                if window.is_key_down(Key::Enter) {
+                render_victory_screen(&mut framebuffer.view(screen), &victory_texture);
+                if input.is_pressed(Key::Enter) {
+                    let stats = &campaign.stats;
+                    println!(
+                        "Campaña completada: {} niveles, {} derrotas, {:.1} s",
+                        stats.levels_completed,
+                        stats.deaths,
+                        stats.total_time.as_secs_f32()
+                    );
                     std::process::exit(0);
                 }
             }
             GameState::Defeat => {
-                render_defeat_screen(&mut framebuffer, &defeat_texture);
-                if window.is_key_down(Key::Enter) {
+                render_defeat_screen(&mut framebuffer.view(screen), &defeat_texture);
+                if input.is_pressed(Key::Enter) {
                     game_state = GameState::StartScreen;
                 }
             }
@@ -1,12 +1,711 @@
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt;
 use std::fs::File;
-use std::io::{BufRead,BufReader};
+use std::io::{self, BufRead, BufReader};
+use nalgebra_glm::Vec2;
+use crate::color::Color;
+use crate::texture::{Filter, Sampler, WrapMode};
 
-pub fn load_maze(filename: &str) -> Vec<Vec<char>> {
-    let file = File::open(filename).unwrap();
+// Radio máximo del jugador en bloques, para que quepa con holgura en un pasillo de una celda
+const MAX_PLAYER_RADIUS: f32 = 0.45;
+
+// Comportamiento por defecto de los fantasmas cuando no persiguen ni huyen
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GhostBehavior {
+    Wander,
+    Patrol,
+}
+
+// Niebla por distancia en la vista 3D; las distancias están en bloques
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Fog {
+    None,
+    Linear { start: f32, end: f32 }, // Sin niebla antes de `start` y opaca desde `end`
+    Exponential { density: f32 },
+}
+
+impl Fog {
+    // Proporción de niebla a una distancia: 0 deja el color intacto y 1 lo cubre por completo
+    pub fn amount(self, distance: f32) -> f32 {
+        match self {
+            Fog::None => 0.0,
+            Fog::Linear { start, end } => ((distance - start) / (end - start).max(f32::EPSILON)).clamp(0.0, 1.0),
+            Fog::Exponential { density } => 1.0 - (-density * distance).exp(),
+        }
+    }
+}
+
+// Fuente de luz fija del nivel (antorchas, el brillo de la salida)
+#[derive(Clone, Copy)]
+pub struct Light {
+    pub cell: (usize, usize), // Como (columna, fila)
+    pub radius: f32,          // Alcance en bloques
+    pub color: Color,
+}
+
+// Reglas de aparición y comportamiento de los fantasmas
+pub struct GhostRules {
+    pub count: usize,
+    pub respawn_secs: f32,
+    pub min_distance: f32, // En bloques
+    pub max_distance: f32, // En bloques
+    pub behavior: GhostBehavior,
+    pub speed: f32,        // En bloques por segundo
+    pub sight: f32,        // Distancia máxima a la que ven al jugador, en bloques
+}
+
+// Nivel completo: cuadrícula más los metadatos declarados en la cabecera del archivo
+pub struct Level {
+    pub name: String,
+    pub grid: Vec<Vec<char>>,
+    pub block_size: usize,
+    pub spawn: (usize, usize), // Celda inicial del jugador como (columna, fila)
+    pub facing: f32,           // Dirección inicial del jugador en radianes
+    pub player_radius: f32,    // Radio de colisión del jugador, en bloques (menos de 0.5)
+    pub exit: (usize, usize),
+    pub ghosts: GhostRules,
+    pub ghost_spawns: Vec<(usize, usize)>,
+    pub legend: HashMap<char, String>, // Carácter de pared -> archivo de textura
+    pub floor_legend: HashMap<char, String>,   // Carácter de suelo -> textura del suelo; ' ' es el suelo por defecto
+    pub ceiling_legend: HashMap<char, String>, // Carácter de suelo -> textura del techo
+    pub floor_plan: Vec<Vec<char>>, // Carácter de suelo de cada celda transitable (vacío en niveles generados)
+    pub music: Option<String>,
+    pub sky_top: Color,
+    pub sky_bottom: Color,
+    pub ground: Color,
+    pub wall_sampler: Sampler, // Filtro y envoltura de las texturas de pared en la vista 3D
+    pub fog: Fog,              // Su color es el del horizonte (`sky_bottom`)
+    pub side_shade: f32,       // Brillo de las caras norte y sur respecto a las caras este y oeste
+    pub ambient: f32,          // Luz base de todas las celdas; 1.0 muestra las texturas sin oscurecer
+    pub lights: Vec<Light>,
+}
+
+impl Level {
+    // Nivel con los valores por defecto para una cuadrícula dada
+    pub fn new(name: &str, grid: Vec<Vec<char>>) -> Self {
+        Self {
+            name: name.to_string(),
+            grid,
+            block_size: 50,
+            spawn: (0, 0),
+            facing: 0.0,
+            exit: (0, 0),
+            ghosts: GhostRules {
+                count: 5,
+                respawn_secs: 7.0,
+                min_distance: 3.0,
+                max_distance: 9.0,
+                behavior: GhostBehavior::Wander,
+                speed: 1.5,
+                sight: 6.0,
+            },
+            ghost_spawns: Vec::new(),
+            player_radius: 0.24,
+            legend: HashMap::from([
+                ('+', "wall1".to_string()),
+                ('-', "wall3".to_string()),
+                ('|', "wall2".to_string()),
+            ]),
+            floor_legend: HashMap::new(),
+            ceiling_legend: HashMap::new(),
+            floor_plan: Vec::new(),
+            music: None,
+            sky_top: Color::new(0, 0, 0),
+            sky_bottom: Color::new(32, 30, 67),
+            ground: Color::ground(),
+            wall_sampler: Sampler::new(Filter::Bilinear, WrapMode::Repeat),
+            fog: Fog::None,
+            side_shade: 0.8,
+            ambient: 1.0,
+            lights: Vec::new(),
+        }
+    }
+
+    pub fn cell_center(&self, cell: (usize, usize)) -> Vec2 {
+        let block = self.block_size as f32;
+        Vec2::new((cell.0 as f32 + 0.5) * block, (cell.1 as f32 + 0.5) * block)
+    }
+
+    pub fn spawn_position(&self) -> Vec2 {
+        self.cell_center(self.spawn)
+    }
+
+    // Radio del jugador en unidades del mundo; siempre cabe en un pasillo de una celda
+    pub fn player_radius(&self) -> f32 {
+        self.player_radius.min(MAX_PLAYER_RADIUS) * self.block_size as f32
+    }
+
+    // Carácter de suelo de la celda (columna, fila); ' ' si no tiene uno propio
+    pub fn floor_glyph(&self, i: isize, j: isize) -> char {
+        cell_at(&self.floor_plan, i, j).unwrap_or(' ')
+    }
+
+    pub fn exit_position(&self) -> Vec2 {
+        self.cell_center(self.exit)
+    }
+}
+
+// Errores al cargar un nivel; las líneas y columnas empiezan en 1 y se refieren al archivo
+#[derive(Debug)]
+pub enum MazeError {
+    Io(io::Error),
+    Header { line: usize, message: String },
+    Empty,
+    NotRectangular { line: usize, expected: usize, found: usize },
+    UnknownGlyph { line: usize, column: usize, glyph: char },
+    OpenBorder { line: usize, column: usize },
+    SpawnInWall { line: usize, column: usize },
+    ExitInWall { line: usize, column: usize },
+    ExitUnreachable { line: usize, column: usize },
+    CellOutOfRange { line: usize, key: String, cell: (usize, usize) }, // Celda de la cabecera fuera de la cuadrícula
+}
+
+impl fmt::Display for MazeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MazeError::Io(err) => write!(f, "no se pudo leer el archivo: {}", err),
+            MazeError::Header { line, message } => write!(f, "línea {}: {}", line, message),
+            MazeError::Empty => write!(f, "el laberinto está vacío"),
+            MazeError::NotRectangular { line, expected, found } => write!(
+                f,
+                "línea {}: la fila tiene {} columnas pero se esperaban {}",
+                line, found, expected
+            ),
+            MazeError::UnknownGlyph { line, column, glyph } => write!(
+                f,
+                "línea {}, columna {}: carácter desconocido '{}' (no está en la leyenda)",
+                line, column, glyph
+            ),
+            MazeError::OpenBorder { line, column } => write!(
+                f,
+                "línea {}, columna {}: el borde del laberinto tiene una abertura",
+                line, column
+            ),
+            MazeError::SpawnInWall { line, column } => write!(
+                f,
+                "línea {}, columna {}: el inicio del jugador está dentro de una pared",
+                line, column
+            ),
+            MazeError::ExitInWall { line, column } => write!(
+                f,
+                "línea {}, columna {}: la salida está dentro de una pared",
+                line, column
+            ),
+            MazeError::ExitUnreachable { line, column } => write!(
+                f,
+                "línea {}, columna {}: la salida no se puede alcanzar desde el inicio",
+                line, column
+            ),
+            MazeError::CellOutOfRange { line, key, cell } => write!(
+                f,
+                "línea {}: la celda {}, {} de `{}` está fuera del laberinto",
+                line, cell.0, cell.1, key
+            ),
+        }
+    }
+}
+
+impl Error for MazeError {}
+
+impl From<io::Error> for MazeError {
+    fn from(err: io::Error) -> Self {
+        MazeError::Io(err)
+    }
+}
+
+// Formato del archivo:
+//
+//   name = Epiphany
+//   facing = 60
+//   texture + = wall1
+//   floor , = carpet
+//   ---
+//   +--+--+
+//   |P ,,X|
+//   +--+--+
+//
+// La cabecera `clave = valor` es opcional y termina con una línea `---`.
+// En la cuadrícula, `P` marca el inicio del jugador, `X` la salida y `G` la aparición de fantasmas;
+// todas se reemplazan por espacio vacío al cargar, igual que los caracteres de suelo declarados
+// con `floor` o `ceiling`, que solo cambian las texturas del suelo y el techo de esa celda. El inicio y la salida también pueden
+// declararse en la cabecera con `spawn = columna, fila` y `exit = columna, fila`.
+pub fn load_maze(filename: &str) -> Result<Level, MazeError> {
+    let file = File::open(filename)?;
     let reader = BufReader::new(file);
 
-    reader
-        .lines()
-        .map(|line| line.unwrap().chars().collect())
-        .collect()
+    let lines = reader.lines().collect::<Result<Vec<String>, io::Error>>()?;
+    parse_maze(filename, &lines)
+}
+
+// Interpreta las líneas de un archivo de nivel; `filename` solo da el nombre por defecto
+fn parse_maze(filename: &str, lines: &[String]) -> Result<Level, MazeError> {
+    // Si no hay separador, todo el archivo es la cuadrícula
+    let (header, grid_lines, grid_start) = match lines.iter().position(|line| line.trim() == "---") {
+        Some(separator) => (&lines[..separator], &lines[separator + 1..], separator + 1),
+        None => (&lines[..0], lines, 0),
+    };
+
+    let mut level = Level::new(filename, Vec::new());
+
+    let mut spawn = None;
+    let mut exit = None;
+    let mut exit_light = None;
+    // Celdas declaradas en la cabecera, con su línea y su clave, para comprobarlas contra la cuadrícula
+    let mut header_cells: Vec<(usize, String, (usize, usize))> = Vec::new();
+
+    for (index, line) in header.iter().enumerate() {
+        let line_number = index + 1;
+        let header_error = |message: String| MazeError::Header { line: line_number, message };
+
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (key, value) = line
+            .split_once('=')
+            .ok_or_else(|| header_error("se esperaba `clave = valor`".to_string()))?;
+        let (key, value) = (key.trim(), value.trim());
+
+        let invalid = |what: &str| header_error(format!("valor inválido para `{}`: {}", what, value));
+
+        match key {
+            "name" => level.name = value.to_string(),
+            "block_size" => {
+                level.block_size = value.parse().map_err(|_| invalid(key))?;
+                if level.block_size == 0 {
+                    return Err(invalid(key));
+                }
+            }
+            "facing" => level.facing = value.parse::<f32>().map_err(|_| invalid(key))?.to_radians(),
+            "spawn" | "exit" => {
+                let cell = parse_cell(value).ok_or_else(|| invalid(key))?;
+                if key == "spawn" {
+                    spawn = Some(cell);
+                } else {
+                    exit = Some(cell);
+                }
+                header_cells.retain(|(_, previous, _)| previous != key);
+                header_cells.push((line_number, key.to_string(), cell));
+            }
+            "ghosts" => level.ghosts.count = value.parse().map_err(|_| invalid(key))?,
+            "ghost_respawn" => level.ghosts.respawn_secs = value.parse().map_err(|_| invalid(key))?,
+            "ghost_distance" => {
+                let (min, max) = value.split_once("..").ok_or_else(|| invalid(key))?;
+                level.ghosts.min_distance = min.trim().parse().map_err(|_| invalid(key))?;
+                level.ghosts.max_distance = max.trim().parse().map_err(|_| invalid(key))?;
+                // El rango de reaparición no puede estar vacío ni ser negativo
+                if !(0.0 <= level.ghosts.min_distance && level.ghosts.min_distance < level.ghosts.max_distance) {
+                    return Err(invalid(key));
+                }
+            }
+            "ghost_behavior" => {
+                level.ghosts.behavior = match value {
+                    "wander" => GhostBehavior::Wander,
+                    "patrol" => GhostBehavior::Patrol,
+                    _ => return Err(invalid(key)),
+                }
+            }
+            "ghost_speed" => level.ghosts.speed = value.parse().map_err(|_| invalid(key))?,
+            "ghost_sight" => level.ghosts.sight = value.parse().map_err(|_| invalid(key))?,
+            "music" => level.music = Some(value.to_string()),
+            "sky" => {
+                let (top, bottom) = value.split_once(' ').ok_or_else(|| invalid(key))?;
+                level.sky_top = parse_color(top).ok_or_else(|| invalid(key))?;
+                level.sky_bottom = parse_color(bottom.trim()).ok_or_else(|| invalid(key))?;
+            }
+            "ground" => level.ground = parse_color(value).ok_or_else(|| invalid(key))?,
+            "texture_filter" => {
+                level.wall_sampler.filter = match value {
+                    "nearest" => Filter::Nearest,
+                    "bilinear" => Filter::Bilinear,
+                    _ => return Err(invalid(key)),
+                }
+            }
+            "texture_wrap" => {
+                level.wall_sampler.wrap = match value {
+                    "clamp" => WrapMode::Clamp,
+                    "repeat" => WrapMode::Repeat,
+                    "mirror" => WrapMode::Mirror,
+                    _ => return Err(invalid(key)),
+                }
+            }
+            "fog" => {
+                let mut parts = value.split_whitespace();
+                level.fog = match (parts.next(), parts.next()) {
+                    (Some("none"), None) => Fog::None,
+                    (Some("linear"), Some(range)) => {
+                        let (start, end) = range.split_once("..").ok_or_else(|| invalid(key))?;
+                        Fog::Linear {
+                            start: start.trim().parse().map_err(|_| invalid(key))?,
+                            end: end.trim().parse().map_err(|_| invalid(key))?,
+                        }
+                    }
+                    (Some("exp"), Some(density)) => Fog::Exponential {
+                        density: density.parse().map_err(|_| invalid(key))?,
+                    },
+                    _ => return Err(invalid(key)),
+                }
+            }
+            "player_radius" => {
+                level.player_radius = value.parse().map_err(|_| invalid(key))?;
+                if !(level.player_radius > 0.0 && level.player_radius <= MAX_PLAYER_RADIUS) {
+                    return Err(invalid(key));
+                }
+            }
+            "side_shade" => level.side_shade = value.parse().map_err(|_| invalid(key))?,
+            "ambient" => level.ambient = value.parse().map_err(|_| invalid(key))?,
+            "exit_light" => exit_light = Some(parse_light(value).ok_or_else(|| invalid(key))?),
+            "floor" => {
+                level.floor_legend.insert(' ', value.to_string());
+            }
+            "ceiling" => {
+                level.ceiling_legend.insert(' ', value.to_string());
+            }
+            _ if key.starts_with("light ") => {
+                // Fuente de luz: `light columna, fila = radio #RRGGBB`
+                let cell = parse_cell(&key["light ".len()..]).ok_or_else(|| invalid(key))?;
+                let (radius, color) = parse_light(value).ok_or_else(|| invalid(key))?;
+                level.lights.push(Light { cell, radius, color });
+                header_cells.push((line_number, key.to_string(), cell));
+            }
+            _ => {
+                // Leyenda: `texture <carácter> = <archivo>` para paredes,
+                // `floor <carácter> = <archivo>` y `ceiling <carácter> = <archivo>` para suelos
+                let (kind, glyph) = key
+                    .split_once(' ')
+                    .ok_or_else(|| header_error(format!("clave desconocida `{}`", key)))?;
+                let glyph = glyph.trim().chars().next().ok_or_else(|| invalid(key))?;
+                let legend = match kind {
+                    "texture" => &mut level.legend,
+                    "floor" => &mut level.floor_legend,
+                    "ceiling" => &mut level.ceiling_legend,
+                    _ => return Err(header_error(format!("clave desconocida `{}`", key))),
+                };
+                legend.insert(glyph, value.to_string());
+            }
+        }
+    }
+
+    // Ignorar las líneas vacías al principio y al final de la cuadrícula
+    let grid_first = grid_lines.iter().position(|line| !line.trim().is_empty()).unwrap_or(0);
+    let grid_end = grid_lines
+        .iter()
+        .rposition(|line| !line.trim().is_empty())
+        .map_or(0, |last| last + 1);
+    let grid_start = grid_start + grid_first;
+
+    for (row, line) in grid_lines[grid_first..grid_end].iter().enumerate() {
+        let mut cells: Vec<char> = line.chars().collect();
+        let mut floors = vec![' '; cells.len()];
+        for (col, cell) in cells.iter_mut().enumerate() {
+            match *cell {
+                // Un marcador en la cuadrícula reemplaza la celda de la cabecera
+                'P' => {
+                    spawn = Some((col, row));
+                    header_cells.retain(|(_, key, _)| key != "spawn");
+                }
+                'X' => {
+                    exit = Some((col, row));
+                    header_cells.retain(|(_, key, _)| key != "exit");
+                }
+                'G' => level.ghost_spawns.push((col, row)),
+                ' ' => continue,
+                glyph if level.legend.contains_key(&glyph) => continue,
+                // Los suelos con textura propia son celdas vacías para el resto del juego
+                glyph if level.floor_legend.contains_key(&glyph) || level.ceiling_legend.contains_key(&glyph) => {
+                    floors[col] = glyph
+                }
+                glyph => {
+                    return Err(MazeError::UnknownGlyph {
+                        line: grid_start + row + 1,
+                        column: col + 1,
+                        glyph,
+                    })
+                }
+            }
+            *cell = ' ';
+        }
+        level.grid.push(cells);
+        level.floor_plan.push(floors);
+    }
+
+    // Sin marcadores, el jugador empieza en la primera celda libre y la salida es la última
+    let free_cells: Vec<(usize, usize)> = level
+        .grid
+        .iter()
+        .enumerate()
+        .flat_map(|(row, cells)| {
+            cells
+                .iter()
+                .enumerate()
+                .filter(|(_, cell)| **cell == ' ')
+                .map(move |(col, _)| (col, row))
+        })
+        .collect();
+    // Las celdas de la cabecera se comprueban una vez que se sabe que la cuadrícula es rectangular
+    validate_shape(&level.grid, grid_start)?;
+    for (line, key, cell) in header_cells {
+        if cell_at(&level.grid, cell.0 as isize, cell.1 as isize).is_none() {
+            return Err(MazeError::CellOutOfRange { line, key, cell });
+        }
+    }
+
+    level.spawn = spawn.or(free_cells.first().copied()).unwrap_or((0, 0));
+    level.exit = exit.or(free_cells.last().copied()).unwrap_or((0, 0));
+
+    // La salida brilla con `exit_light = radio #RRGGBB`
+    if let Some((radius, color)) = exit_light {
+        level.lights.push(Light { cell: level.exit, radius, color });
+    }
+
+    validate(&level, grid_start)?;
+
+    Ok(level)
+}
+
+// Comprueba que el laberinto sea rectangular, cerrado y que la salida sea alcanzable
+pub(crate) fn validate(level: &Level, grid_start: usize) -> Result<(), MazeError> {
+    let grid = &level.grid;
+    let position = |(col, row): (usize, usize)| (grid_start + row + 1, col + 1);
+
+    validate_shape(grid, grid_start)?;
+
+    let width = grid[0].len();
+    let height = grid.len();
+    for (row, cells) in grid.iter().enumerate() {
+        for (col, cell) in cells.iter().enumerate() {
+            let on_border = row == 0 || row == height - 1 || col == 0 || col == width - 1;
+            if on_border && *cell == ' ' {
+                let (line, column) = position((col, row));
+                return Err(MazeError::OpenBorder { line, column });
+            }
+        }
+    }
+
+    let (spawn_col, spawn_row) = level.spawn;
+    if !is_walkable(grid, spawn_col as isize, spawn_row as isize) {
+        let (line, column) = position(level.spawn);
+        return Err(MazeError::SpawnInWall { line, column });
+    }
+
+    let (exit_col, exit_row) = level.exit;
+    if !is_walkable(grid, exit_col as isize, exit_row as isize) {
+        let (line, column) = position(level.exit);
+        return Err(MazeError::ExitInWall { line, column });
+    }
+
+    if !flood_fill(grid, level.spawn).contains(&level.exit) {
+        let (line, column) = position(level.exit);
+        return Err(MazeError::ExitUnreachable { line, column });
+    }
+
+    Ok(())
+}
+
+// Comprueba que la cuadrícula no esté vacía y que todas sus filas tengan el mismo largo
+fn validate_shape(grid: &[Vec<char>], grid_start: usize) -> Result<(), MazeError> {
+    let width = grid.first().map_or(0, |row| row.len());
+    if width == 0 {
+        return Err(MazeError::Empty);
+    }
+
+    for (row, cells) in grid.iter().enumerate() {
+        if cells.len() != width {
+            return Err(MazeError::NotRectangular {
+                line: grid_start + row + 1,
+                expected: width,
+                found: cells.len(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+// Devuelve todas las celdas transitables alcanzables desde `start`
+fn flood_fill(grid: &[Vec<char>], start: (usize, usize)) -> HashSet<(usize, usize)> {
+    let mut visited = HashSet::from([start]);
+    let mut pending = vec![start];
+
+    while let Some((col, row)) = pending.pop() {
+        let neighbors = [(0, -1), (0, 1), (-1, 0), (1, 0)];
+        for (dx, dy) in neighbors {
+            let i = col as isize + dx;
+            let j = row as isize + dy;
+            if is_walkable(grid, i, j) && visited.insert((i as usize, j as usize)) {
+                pending.push((i as usize, j as usize));
+            }
+        }
+    }
+
+    visited
+}
+
+// Convierte "columna, fila" en una celda
+fn parse_cell(value: &str) -> Option<(usize, usize)> {
+    let (col, row) = value.split_once(',')?;
+    Some((col.trim().parse().ok()?, row.trim().parse().ok()?))
+}
+
+// Convierte "radio #RRGGBB" en el alcance y color de una luz
+fn parse_light(value: &str) -> Option<(f32, Color)> {
+    let (radius, color) = value.split_once(' ')?;
+    Some((radius.parse().ok()?, parse_color(color.trim())?))
+}
+
+// Convierte "#RRGGBB" en un color
+fn parse_color(value: &str) -> Option<Color> {
+    let hex = u32::from_str_radix(value.trim_start_matches('#'), 16).ok()?;
+    Some(Color::from_hex(hex))
+}
+
+// Devuelve el carácter de la celda (i, j), o None si está fuera del laberinto
+pub fn cell_at(maze: &[Vec<char>], i: isize, j: isize) -> Option<char> {
+    if i < 0 || j < 0 {
+        return None;
+    }
+    maze.get(j as usize)?.get(i as usize).copied()
+}
+
+// Celda que contiene una posición del mundo, como (columna, fila)
+pub fn position_to_cell(position: Vec2, block_size: usize) -> (isize, isize) {
+    let block = block_size as f32;
+    ((position.x / block).floor() as isize, (position.y / block).floor() as isize)
+}
+
+// Una celda es transitable solo si existe y está vacía; fuera del mapa se considera pared
+pub fn is_walkable(maze: &[Vec<char>], i: isize, j: isize) -> bool {
+    cell_at(maze, i, j) == Some(' ')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Result<Level, MazeError> {
+        let lines: Vec<String> = text.lines().map(str::to_string).collect();
+        parse_maze("test", &lines)
+    }
+
+    // Tipo y posición (línea, columna) del error, para comparar sin depender del mensaje
+    fn error_at(text: &str) -> (&'static str, usize, usize) {
+        let Err(err) = parse(text) else {
+            panic!("el nivel se cargó sin errores");
+        };
+        match err {
+            MazeError::UnknownGlyph { line, column, .. } => ("UnknownGlyph", line, column),
+            MazeError::OpenBorder { line, column } => ("OpenBorder", line, column),
+            MazeError::SpawnInWall { line, column } => ("SpawnInWall", line, column),
+            MazeError::ExitInWall { line, column } => ("ExitInWall", line, column),
+            MazeError::ExitUnreachable { line, column } => ("ExitUnreachable", line, column),
+            other => panic!("error inesperado: {}", other),
+        }
+    }
+
+    const HEADER: &str = "name = Test\nfacing = 0\n---\n";
+
+    #[test]
+    fn valid_level_loads() {
+        let level = parse("+--+\n|PX|\n+--+").unwrap();
+        assert_eq!(level.spawn, (1, 1));
+        assert_eq!(level.exit, (2, 1));
+        assert!(parse(&format!("{}+--+\n|PX|\n+--+", HEADER)).is_ok());
+    }
+
+    // Línea y celda de un error CellOutOfRange, que apunta a la cabecera y no tiene columna
+    fn out_of_range_at(text: &str) -> (usize, String, (usize, usize)) {
+        match parse(text) {
+            Err(MazeError::CellOutOfRange { line, key, cell }) => (line, key, cell),
+            Err(other) => panic!("error inesperado: {}", other),
+            Ok(_) => panic!("el nivel se cargó sin errores"),
+        }
+    }
+
+    #[test]
+    fn ragged_row() {
+        let grid = "+--+\n|PX|\n+-+";
+        for (text, expected_line) in [(grid.to_string(), 3), (format!("{}{}", HEADER, grid), 6)] {
+            let Err(MazeError::NotRectangular { line, expected, found }) = parse(&text) else {
+                panic!("se esperaba NotRectangular");
+            };
+            assert_eq!(line, expected_line);
+            assert_eq!(expected, 4);
+            assert_eq!(found, 3);
+        }
+    }
+
+    #[test]
+    fn blank_lines_around_the_grid_are_ignored() {
+        let level = parse(&format!("{}\n\n+--+\n|PX|\n+--+\n\n", HEADER)).unwrap();
+        assert_eq!(level.grid.len(), 3);
+        assert_eq!(level.spawn, (1, 1));
+        // Las líneas de los errores siguen contando las vacías del principio
+        assert_eq!(error_at(&format!("{}\n\n+--+\n|P?|\n|X |\n+--+", HEADER)), ("UnknownGlyph", 7, 3));
+    }
+
+    #[test]
+    fn unknown_glyph() {
+        let grid = "+--+\n|P?|\n|X |\n+--+";
+        assert_eq!(error_at(grid), ("UnknownGlyph", 2, 3));
+        assert_eq!(error_at(&format!("{}{}", HEADER, grid)), ("UnknownGlyph", 5, 3));
+    }
+
+    #[test]
+    fn open_border() {
+        let grid = "+--+\n|PX|\n+- +";
+        assert_eq!(error_at(grid), ("OpenBorder", 3, 3));
+        assert_eq!(error_at(&format!("{}{}", HEADER, grid)), ("OpenBorder", 6, 3));
+    }
+
+    #[test]
+    fn spawn_in_wall() {
+        let grid = "+---+\n|  X|\n+---+";
+        assert_eq!(error_at(&format!("spawn = 2, 0\n---\n{}", grid)), ("SpawnInWall", 3, 3));
+        assert_eq!(
+            error_at(&format!("name = Test\nspawn = 4, 1\n---\n{}", grid)),
+            ("SpawnInWall", 5, 5)
+        );
+    }
+
+    #[test]
+    fn exit_in_wall() {
+        let grid = "+---+\n|P  |\n+---+";
+        assert_eq!(error_at(&format!("exit = 4, 1\n---\n{}", grid)), ("ExitInWall", 4, 5));
+        assert_eq!(
+            error_at(&format!("name = Test\nexit = 1, 2\n---\n{}", grid)),
+            ("ExitInWall", 6, 2)
+        );
+    }
+
+    #[test]
+    fn header_cells_outside_the_grid() {
+        let grid = "+---+\n|P X|\n+---+";
+        assert_eq!(
+            out_of_range_at("name = Test\nspawn = 9, 1\n---\n+---+\n|  X|\n+---+"),
+            (2, "spawn".to_string(), (9, 1))
+        );
+        assert_eq!(
+            out_of_range_at("exit = 1, 7\n---\n+---+\n|P  |\n+---+"),
+            (1, "exit".to_string(), (1, 7))
+        );
+        assert_eq!(
+            out_of_range_at(&format!("name = Test\nlight 5, 1 = 3 #FFFFFF\n---\n{}", grid)),
+            (2, "light 5, 1".to_string(), (5, 1))
+        );
+        // La luz de la salida está en la salida, así que una salida fuera del mapa se informa en su línea
+        assert_eq!(
+            out_of_range_at("exit_light = 3 #FFCC00\nexit = 4, 3\n---\n+---+\n|P  |\n+---+"),
+            (2, "exit".to_string(), (4, 3))
+        );
+        // Un marcador de la cuadrícula reemplaza la celda de la cabecera
+        assert!(parse(&format!("spawn = 9, 9\n---\n{}", grid)).is_ok());
+    }
+
+    #[test]
+    fn exit_unreachable() {
+        let grid = "+---+\n|P|X|\n+---+";
+        assert_eq!(error_at(grid), ("ExitUnreachable", 2, 4));
+        assert_eq!(error_at(&format!("{}{}", HEADER, grid)), ("ExitUnreachable", 5, 4));
+    }
 }
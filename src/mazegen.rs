@@ -0,0 +1,405 @@
+use std::fmt;
+use std::str::FromStr;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use crate::maze::Level;
+
+// Algoritmos disponibles para generar el laberinto
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Algorithm {
+    RecursiveBacktracker,
+    Prim,
+    Kruskal,
+    Wilson,
+}
+
+impl FromStr for Algorithm {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.to_lowercase().as_str() {
+            "backtracker" | "recursive-backtracker" => Ok(Algorithm::RecursiveBacktracker),
+            "prim" => Ok(Algorithm::Prim),
+            "kruskal" => Ok(Algorithm::Kruskal),
+            "wilson" => Ok(Algorithm::Wilson),
+            _ => Err(format!("algoritmo desconocido: {}", name)),
+        }
+    }
+}
+
+impl fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Algorithm::RecursiveBacktracker => "backtracker",
+            Algorithm::Prim => "prim",
+            Algorithm::Kruskal => "kruskal",
+            Algorithm::Wilson => "wilson",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+pub struct GeneratorOptions {
+    pub width: usize,  // Ancho en celdas (no en caracteres)
+    pub height: usize, // Alto en celdas
+    pub seed: u64,
+    pub algorithm: Algorithm,
+    pub braid: f32,    // Probabilidad (0..1) de abrir cada callejón sin salida, creando ciclos
+    pub rooms: usize,  // Número de habitaciones rectangulares a excavar
+}
+
+impl GeneratorOptions {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            width: 6,
+            height: 6,
+            seed,
+            algorithm: Algorithm::RecursiveBacktracker,
+            braid: 0.0,
+            rooms: 0,
+        }
+    }
+}
+
+// Paredes entre celdas: `east[y][x]` separa (x, y) de (x + 1, y) y `south[y][x]` separa (x, y) de (x, y + 1)
+struct Cells {
+    width: usize,
+    height: usize,
+    east: Vec<Vec<bool>>,
+    south: Vec<Vec<bool>>,
+}
+
+impl Cells {
+    fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            east: vec![vec![true; width]; height],
+            south: vec![vec![true; width]; height],
+        }
+    }
+
+    fn neighbors(&self, (x, y): (usize, usize)) -> Vec<(usize, usize)> {
+        let mut neighbors = Vec::with_capacity(4);
+        if x > 0 {
+            neighbors.push((x - 1, y));
+        }
+        if x + 1 < self.width {
+            neighbors.push((x + 1, y));
+        }
+        if y > 0 {
+            neighbors.push((x, y - 1));
+        }
+        if y + 1 < self.height {
+            neighbors.push((x, y + 1));
+        }
+        neighbors
+    }
+
+    // Quita la pared entre dos celdas vecinas
+    fn carve(&mut self, (x, y): (usize, usize), (nx, ny): (usize, usize)) {
+        if y == ny {
+            self.east[y][x.min(nx)] = false;
+        } else {
+            self.south[y.min(ny)][x] = false;
+        }
+    }
+
+    fn is_open(&self, (x, y): (usize, usize), (nx, ny): (usize, usize)) -> bool {
+        if y == ny {
+            !self.east[y][x.min(nx)]
+        } else {
+            !self.south[y.min(ny)][x]
+        }
+    }
+
+    fn index(&self, (x, y): (usize, usize)) -> usize {
+        y * self.width + x
+    }
+
+    // Convierte las celdas a la convención de `maze.txt`: celdas de 3x2 caracteres con `+`, `-` y `|`
+    fn to_grid(&self) -> Vec<Vec<char>> {
+        let mut grid = Vec::with_capacity(self.height * 2 + 1);
+
+        for y in 0..=self.height {
+            // Fila de paredes horizontales
+            let mut row = Vec::with_capacity(self.width * 3 + 1);
+            for x in 0..self.width {
+                let wall = y == 0 || y == self.height || self.south[y - 1][x];
+                row.push('+');
+                row.extend(if wall { ['-', '-'] } else { [' ', ' '] });
+            }
+            row.push('+');
+            grid.push(row);
+
+            if y == self.height {
+                break;
+            }
+
+            // Fila de celdas con sus paredes verticales
+            let mut row = Vec::with_capacity(self.width * 3 + 1);
+            row.push('|');
+            for x in 0..self.width {
+                row.extend([' ', ' ']);
+                let wall = x == self.width - 1 || self.east[y][x];
+                row.push(if wall { '|' } else { ' ' });
+            }
+            grid.push(row);
+        }
+
+        grid
+    }
+}
+
+// Genera una cuadrícula compatible con `maze.txt`
+pub fn generate(options: &GeneratorOptions) -> Vec<Vec<char>> {
+    let mut rng = StdRng::seed_from_u64(options.seed);
+    let mut cells = Cells::new(options.width.max(1), options.height.max(1));
+
+    match options.algorithm {
+        Algorithm::RecursiveBacktracker => recursive_backtracker(&mut cells, &mut rng),
+        Algorithm::Prim => prim(&mut cells, &mut rng),
+        Algorithm::Kruskal => kruskal(&mut cells, &mut rng),
+        Algorithm::Wilson => wilson(&mut cells, &mut rng),
+    }
+
+    carve_rooms(&mut cells, options.rooms, &mut rng);
+    braid(&mut cells, options.braid, &mut rng);
+
+    cells.to_grid()
+}
+
+// Genera un nivel completo: el jugador empieza en la esquina superior izquierda y sale por la opuesta
+pub fn generate_level(options: &GeneratorOptions) -> Level {
+    let name = format!("{} #{}", options.algorithm, options.seed);
+    let mut level = Level::new(&name, generate(options));
+
+    let to_grid_cell = |(x, y): (usize, usize)| (x * 3 + 1, y * 2 + 1);
+    level.spawn = to_grid_cell((0, 0));
+    level.exit = to_grid_cell((options.width.max(1) - 1, options.height.max(1) - 1));
+
+    level
+}
+
+fn recursive_backtracker(cells: &mut Cells, rng: &mut StdRng) {
+    let mut visited = vec![false; cells.width * cells.height];
+    let start = (rng.gen_range(0..cells.width), rng.gen_range(0..cells.height));
+    let mut stack = vec![start];
+    visited[cells.index(start)] = true;
+
+    while let Some(&current) = stack.last() {
+        let unvisited: Vec<_> = cells
+            .neighbors(current)
+            .into_iter()
+            .filter(|&cell| !visited[cells.index(cell)])
+            .collect();
+
+        match unvisited.choose(rng) {
+            Some(&next) => {
+                cells.carve(current, next);
+                visited[cells.index(next)] = true;
+                stack.push(next);
+            }
+            None => {
+                stack.pop();
+            }
+        }
+    }
+}
+
+fn prim(cells: &mut Cells, rng: &mut StdRng) {
+    let mut visited = vec![false; cells.width * cells.height];
+    let start = (rng.gen_range(0..cells.width), rng.gen_range(0..cells.height));
+    visited[cells.index(start)] = true;
+
+    let mut frontier = cells.neighbors(start);
+
+    while !frontier.is_empty() {
+        let cell = frontier.swap_remove(rng.gen_range(0..frontier.len()));
+        if visited[cells.index(cell)] {
+            continue;
+        }
+
+        // Conectar la celda con un vecino que ya forme parte del laberinto
+        let inside: Vec<_> = cells
+            .neighbors(cell)
+            .into_iter()
+            .filter(|&neighbor| visited[cells.index(neighbor)])
+            .collect();
+        if let Some(&neighbor) = inside.choose(rng) {
+            cells.carve(cell, neighbor);
+        }
+        visited[cells.index(cell)] = true;
+
+        for neighbor in cells.neighbors(cell) {
+            if !visited[cells.index(neighbor)] {
+                frontier.push(neighbor);
+            }
+        }
+    }
+}
+
+fn kruskal(cells: &mut Cells, rng: &mut StdRng) {
+    let mut edges = Vec::new();
+    for y in 0..cells.height {
+        for x in 0..cells.width {
+            if x + 1 < cells.width {
+                edges.push(((x, y), (x + 1, y)));
+            }
+            if y + 1 < cells.height {
+                edges.push(((x, y), (x, y + 1)));
+            }
+        }
+    }
+    edges.shuffle(rng);
+
+    // Conjuntos disjuntos (union-find) para evitar ciclos
+    let mut parent: Vec<usize> = (0..cells.width * cells.height).collect();
+    fn find(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+
+    for (a, b) in edges {
+        let root_a = find(&mut parent, cells.index(a));
+        let root_b = find(&mut parent, cells.index(b));
+        if root_a != root_b {
+            parent[root_a] = root_b;
+            cells.carve(a, b);
+        }
+    }
+}
+
+fn wilson(cells: &mut Cells, rng: &mut StdRng) {
+    let total = cells.width * cells.height;
+    let mut in_maze = vec![false; total];
+    let first = (rng.gen_range(0..cells.width), rng.gen_range(0..cells.height));
+    in_maze[cells.index(first)] = true;
+    let mut remaining = total - 1;
+
+    // Próximo paso de la caminata aleatoria desde cada celda
+    let mut next: Vec<Option<(usize, usize)>> = vec![None; total];
+
+    while remaining > 0 {
+        let outside: Vec<_> = (0..total).filter(|&i| !in_maze[i]).collect();
+        let start_index = *outside.choose(rng).unwrap();
+        let start = (start_index % cells.width, start_index / cells.width);
+
+        // Caminata aleatoria hasta tocar el laberinto; sobrescribir `next` borra los bucles
+        let mut current = start;
+        while !in_maze[cells.index(current)] {
+            let step = *cells.neighbors(current).choose(rng).unwrap();
+            next[cells.index(current)] = Some(step);
+            current = step;
+        }
+
+        // Añadir el camino sin bucles al laberinto
+        let mut current = start;
+        while !in_maze[cells.index(current)] {
+            let step = next[cells.index(current)].unwrap();
+            cells.carve(current, step);
+            in_maze[cells.index(current)] = true;
+            remaining -= 1;
+            current = step;
+        }
+    }
+}
+
+// Excava habitaciones rectangulares quitando las paredes interiores
+fn carve_rooms(cells: &mut Cells, rooms: usize, rng: &mut StdRng) {
+    for _ in 0..rooms {
+        let room_width = rng.gen_range(2..=3).min(cells.width);
+        let room_height = rng.gen_range(2..=3).min(cells.height);
+        let left = rng.gen_range(0..=cells.width - room_width);
+        let top = rng.gen_range(0..=cells.height - room_height);
+
+        for y in top..top + room_height {
+            for x in left..left + room_width {
+                if x + 1 < left + room_width {
+                    cells.carve((x, y), (x + 1, y));
+                }
+                if y + 1 < top + room_height {
+                    cells.carve((x, y), (x, y + 1));
+                }
+            }
+        }
+    }
+}
+
+// Abre callejones sin salida con la probabilidad dada para crear ciclos
+fn braid(cells: &mut Cells, probability: f32, rng: &mut StdRng) {
+    if probability <= 0.0 {
+        return;
+    }
+
+    for y in 0..cells.height {
+        for x in 0..cells.width {
+            let cell = (x, y);
+            let neighbors = cells.neighbors(cell);
+            let open = neighbors.iter().filter(|&&n| cells.is_open(cell, n)).count();
+
+            if open == 1 && rng.gen::<f32>() < probability {
+                let closed: Vec<_> = neighbors
+                    .into_iter()
+                    .filter(|&n| !cells.is_open(cell, n))
+                    .collect();
+                if let Some(&neighbor) = closed.choose(rng) {
+                    cells.carve(cell, neighbor);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::validate;
+
+    const ALGORITHMS: [Algorithm; 4] = [Algorithm::RecursiveBacktracker, Algorithm::Prim, Algorithm::Kruskal, Algorithm::Wilson];
+
+    fn options(algorithm: Algorithm, (width, height): (usize, usize), seed: u64, braid: f32, rooms: usize) -> GeneratorOptions {
+        GeneratorOptions { width, height, seed, algorithm, braid, rooms }
+    }
+
+    #[test]
+    fn generated_levels_are_valid() {
+        for algorithm in ALGORITHMS {
+            for size in [(1, 1), (2, 3), (6, 6), (12, 5)] {
+                for (braid, rooms) in [(0.0, 0), (0.5, 0), (0.0, 3), (1.0, 2)] {
+                    for seed in 0..8 {
+                        let level = generate_level(&options(algorithm, size, seed, braid, rooms));
+                        if let Err(err) = validate(&level, 0) {
+                            panic!("{} {:?} braid {} rooms {} seed {}: {}", algorithm, size, braid, rooms, seed, err);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_same_maze() {
+        for algorithm in ALGORITHMS {
+            for size in [(1, 1), (2, 3), (8, 8)] {
+                for seed in [0, 7, 12345] {
+                    let options = options(algorithm, size, seed, 0.3, 1);
+                    assert_eq!(generate(&options), generate(&options), "{} {:?} seed {}", algorithm, size, seed);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn grid_size_follows_cell_count() {
+        for (width, height) in [(1, 1), (2, 3), (7, 4)] {
+            let grid = generate(&options(Algorithm::Prim, (width, height), 1, 0.0, 0));
+            assert_eq!(grid.len(), height * 2 + 1);
+            assert!(grid.iter().all(|row| row.len() == width * 3 + 1));
+        }
+    }
+}
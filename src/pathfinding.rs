@@ -0,0 +1,107 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+use crate::maze::is_walkable;
+
+// Celda de la cuadrícula como (columna, fila)
+pub type Cell = (usize, usize);
+
+// Busca el camino más corto entre dos celdas transitables con A* (distancia Manhattan).
+// El camino devuelto no incluye la celda inicial pero sí la final, así que queda vacío si
+// ya se está en la meta.
+pub fn find_path(maze: &[Vec<char>], start: Cell, goal: Cell) -> Option<Vec<Cell>> {
+    if !is_walkable(maze, goal.0 as isize, goal.1 as isize) {
+        return None;
+    }
+    if start == goal {
+        return Some(Vec::new());
+    }
+
+    let heuristic = |(col, row): Cell| col.abs_diff(goal.0) + row.abs_diff(goal.1);
+
+    let mut open = BinaryHeap::from([Reverse((heuristic(start), 0, start))]);
+    let mut came_from: HashMap<Cell, Cell> = HashMap::new();
+    let mut cost: HashMap<Cell, usize> = HashMap::from([(start, 0)]);
+
+    while let Some(Reverse((_, current_cost, current))) = open.pop() {
+        if current == goal {
+            // Reconstruir el camino desde la meta hasta el inicio
+            let mut path = vec![current];
+            let mut cell = current;
+            while let Some(&previous) = came_from.get(&cell) {
+                if previous == start {
+                    break;
+                }
+                path.push(previous);
+                cell = previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        if current_cost > cost[&current] {
+            continue;
+        }
+
+        let (col, row) = current;
+        for (dx, dy) in [(0, -1), (0, 1), (-1, 0), (1, 0)] {
+            let i = col as isize + dx;
+            let j = row as isize + dy;
+            if !is_walkable(maze, i, j) {
+                continue;
+            }
+
+            let neighbor = (i as usize, j as usize);
+            let neighbor_cost = current_cost + 1;
+            if cost.get(&neighbor).is_none_or(|&known| neighbor_cost < known) {
+                cost.insert(neighbor, neighbor_cost);
+                came_from.insert(neighbor, current);
+                open.push(Reverse((neighbor_cost + heuristic(neighbor), neighbor_cost, neighbor)));
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn maze(rows: &[&str]) -> Vec<Vec<char>> {
+        rows.iter().map(|row| row.chars().collect()).collect()
+    }
+
+    #[test]
+    fn shortest_path_goes_around_walls() {
+        let maze = maze(&["+-----+", "|  |  |", "|  |  |", "|     |", "+-----+"]);
+        let path = find_path(&maze, (1, 1), (5, 1)).unwrap();
+        // 2 pasos abajo, 4 a la derecha y 2 arriba; sin la celda inicial
+        assert_eq!(path.len(), 8);
+        assert!(!path.contains(&(1, 1)));
+        assert_eq!(path.last(), Some(&(5, 1)));
+        // Cada paso va a una celda libre vecina de la anterior, empezando junto al inicio
+        for (a, b) in std::iter::once(&(1, 1)).chain(&path).zip(&path) {
+            assert_eq!(a.0.abs_diff(b.0) + a.1.abs_diff(b.1), 1);
+            assert!(is_walkable(&maze, b.0 as isize, b.1 as isize));
+        }
+    }
+
+    #[test]
+    fn start_at_the_goal_is_an_empty_path() {
+        let maze = maze(&["+--+", "|  |", "+--+"]);
+        assert_eq!(find_path(&maze, (1, 1), (1, 1)), Some(Vec::new()));
+    }
+
+    #[test]
+    fn unreachable_goal_has_no_path() {
+        let maze = maze(&["+-----+", "|  |  |", "+-----+"]);
+        assert_eq!(find_path(&maze, (1, 1), (4, 1)), None);
+    }
+
+    #[test]
+    fn goal_inside_a_wall_has_no_path() {
+        let maze = maze(&["+---+", "|   |", "+---+"]);
+        assert_eq!(find_path(&maze, (1, 1), (2, 0)), None);
+        assert_eq!(find_path(&maze, (1, 1), (9, 9)), None);
+    }
+}
@@ -3,6 +3,8 @@ use nalgebra_glm::Vec2;
 pub struct Player{
     pub position: Vec2,
     pub a: f32,
-    pub fov: f32
+    pub fov: f32,
+    pub radius: f32, // Radio del círculo de colisión del jugador
+    pub flashlight: bool,
 }
 
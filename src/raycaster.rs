@@ -1,45 +1,190 @@
-use crate::framebuffer::Framebuffer;
-use crate::player::Player;
-use crate::color::Color;
+use nalgebra_glm::Vec2;
+use crate::maze::cell_at;
+
+// Lado de la cuadrícula que golpeó el rayo
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Side {
+    Vertical,   // Cruzó una línea vertical de la cuadrícula (cara este/oeste)
+    Horizontal, // Cruzó una línea horizontal de la cuadrícula (cara norte/sur)
+}
 
 pub struct Intersect{
     pub distance: f32,
-    pub impact: char
+    pub impact: char,
+    pub side: Side,
+    pub hit: Vec2,           // Punto exacto de impacto en coordenadas del mundo
+    pub cell: Option<(usize, usize)>, // Celda golpeada como (columna, fila); None si no hubo impacto
+    pub offset: f32,         // Posición fraccionaria (0..1) del impacto a lo largo de la cara
+}
+
+impl Intersect {
+    // Un rayo que no encontró pared (límite del mapa o distancia máxima) reporta impact ' '
+    pub fn is_hit(&self) -> bool {
+        self.impact != ' '
+    }
 }
 
 pub fn cast_ray(
-    framebuffer: &mut Framebuffer,
-    maze: &Vec<Vec<char>>, 
-    player: &Player, 
+    maze: &[Vec<char>],
+    origin: Vec2,
     angle: f32,
-    block_size: usize, 
-    draw_line: bool,
+    block_size: usize,
+    max_distance: f32,
 ) -> Intersect{
-    let mut d = 0.0;
+    let block = block_size as f32;
+    let dir = Vec2::new(angle.cos(), angle.sin());
 
-    if draw_line {
-        framebuffer.set_current_color(Color::red().to_hex());
-    }
+    // Celda donde empieza el rayo
+    let mut i = (origin.x / block).floor() as isize;
+    let mut j = (origin.y / block).floor() as isize;
+
+    // Distancia que recorre el rayo para cruzar una celda completa en cada eje
+    let delta_x = if dir.x == 0.0 { f32::INFINITY } else { (block / dir.x).abs() };
+    let delta_y = if dir.y == 0.0 { f32::INFINITY } else { (block / dir.y).abs() };
+
+    // Dirección del paso y distancia hasta la primera línea de la cuadrícula en cada eje
+    let (step_i, mut side_x) = if dir.x < 0.0 {
+        (-1, (origin.x - i as f32 * block) / -dir.x)
+    } else {
+        (1, ((i + 1) as f32 * block - origin.x) / dir.x)
+    };
+    let (step_j, mut side_y) = if dir.y < 0.0 {
+        (-1, (origin.y - j as f32 * block) / -dir.y)
+    } else {
+        (1, ((j + 1) as f32 * block - origin.y) / dir.y)
+    };
 
-    loop {
-        let cos_angle = d * angle.cos();
-        let sin_angle = d * angle.sin();
-        let x = (player.position.x + cos_angle) as usize;
-        let y = (player.position.y + sin_angle) as usize;
+    let mut d = 0.0;
+    let mut side = Side::Vertical;
 
-        let i = x / block_size;
-        let j = y / block_size;
+    // Avanzar celda por celda (DDA) hasta encontrar una pared, salir del mapa o superar la distancia máxima
+    let impact = loop {
+        match cell_at(maze, i, j) {
+            Some(' ') => {}
+            Some(cell) => break cell,
+            None => break ' ',
+        }
 
-        if maze[j][i] != ' ' {
-            return Intersect{
-                distance:d,
-                impact: maze[j][i]
-            }
+        if side_x < side_y {
+            d = side_x;
+            side_x += delta_x;
+            i += step_i;
+            side = Side::Vertical;
+        } else {
+            d = side_y;
+            side_y += delta_y;
+            j += step_j;
+            side = Side::Horizontal;
         }
-        if draw_line{
-            framebuffer.point(x, y);
+
+        // Un paso puede saltar más allá del límite; lo que haya detrás no cuenta como impacto
+        if d > max_distance {
+            d = max_distance;
+            break ' ';
         }
+    };
+
+    let hit = origin + dir * d;
+    // cell_at solo devuelve una pared dentro del mapa, así que (i, j) no es negativo
+    let cell = (impact != ' ').then_some((i as usize, j as usize));
+
+    // La coordenada que varía a lo largo de la cara depende del lado golpeado
+    let along = match side {
+        Side::Vertical => hit.y / block,
+        Side::Horizontal => hit.x / block,
+    };
+    let offset = along - along.floor();
+
+    Intersect{
+        distance: d,
+        impact,
+        side,
+        hit,
+        cell,
+        offset,
+    }
+}
+
+// Hay línea de visión si ninguna pared se interpone entre los dos puntos
+pub fn has_line_of_sight(maze: &[Vec<char>], from: Vec2, to: Vec2, block_size: usize) -> bool {
+    let delta = to - from;
+    let distance = delta.norm();
+    let intersect = cast_ray(maze, from, delta.y.atan2(delta.x), block_size, distance);
+    !intersect.is_hit() || intersect.distance >= distance
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    const BLOCK: usize = 10;
+
+    fn maze(rows: &[&str]) -> Vec<Vec<char>> {
+        rows.iter().map(|row| row.chars().collect()).collect()
+    }
+
+    fn room() -> Vec<Vec<char>> {
+        maze(&["#####", "#   #", "#   #", "#   #", "#####"])
+    }
+
+    fn assert_close(actual: f32, expected: f32) {
+        assert!((actual - expected).abs() < 1e-3, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn exact_distance_to_vertical_wall() {
+        let intersect = cast_ray(&room(), Vec2::new(25.0, 23.0), 0.0, BLOCK, 1000.0);
+        assert!(intersect.is_hit());
+        assert_close(intersect.distance, 15.0);
+        assert_eq!(intersect.side, Side::Vertical);
+        assert_eq!(intersect.cell, Some((4, 2)));
+        assert_close(intersect.hit.x, 40.0);
+        assert_close(intersect.offset, 0.3);
+    }
+
+    #[test]
+    fn exact_distance_to_horizontal_wall() {
+        let intersect = cast_ray(&room(), Vec2::new(17.0, 25.0), -PI / 2.0, BLOCK, 1000.0);
+        assert!(intersect.is_hit());
+        assert_close(intersect.distance, 15.0);
+        assert_eq!(intersect.side, Side::Horizontal);
+        assert_eq!(intersect.cell, Some((1, 0)));
+        assert_close(intersect.hit.y, 10.0);
+        assert_close(intersect.offset, 0.7);
+    }
+
+    #[test]
+    fn grazing_a_corner_hits_the_wall() {
+        // La diagonal pasa justo por la esquina superior izquierda del pilar en (3, 3)
+        let pillar = maze(&["#######", "#     #", "#     #", "#  #  #", "#     #", "#######"]);
+        let intersect = cast_ray(&pillar, Vec2::new(15.0, 15.0), PI / 4.0, BLOCK, 1000.0);
+        assert!(intersect.is_hit());
+        assert_eq!(intersect.cell, Some((3, 3)));
+        assert_close(intersect.distance, 15.0 * 2f32.sqrt());
+        assert_close(intersect.hit.x, 30.0);
+        assert_close(intersect.hit.y, 30.0);
+    }
+
+    #[test]
+    fn wall_beyond_max_distance_is_not_a_hit() {
+        // Con bloques de 50 la pared está a 475 unidades, más allá del límite de 440
+        let corridor = maze(&["############", "#          #", "############"]);
+        let intersect = cast_ray(&corridor, Vec2::new(75.0, 75.0), 0.0, 50, 440.0);
+        assert!(!intersect.is_hit());
+        assert_eq!(intersect.cell, None);
+        assert_close(intersect.distance, 440.0);
+
+        let intersect = cast_ray(&corridor, Vec2::new(75.0, 75.0), 0.0, 50, 475.0);
+        assert!(intersect.is_hit());
+        assert_close(intersect.distance, 475.0);
+    }
 
-        d += 0.8;
+    #[test]
+    fn leaving_the_map_is_not_a_hit() {
+        let open = maze(&["     ", "     "]);
+        let intersect = cast_ray(&open, Vec2::new(5.0, 5.0), 0.0, BLOCK, 1000.0);
+        assert!(!intersect.is_hit());
+        assert_eq!(intersect.cell, None);
     }
 }
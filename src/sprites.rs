@@ -0,0 +1,85 @@
+use std::f32::consts::PI;
+use nalgebra_glm::Vec2;
+use crate::framebuffer::{BlendMode, Canvas, FramebufferView};
+use crate::player::Player;
+use crate::texture::Texture;
+
+// Imagen plana que siempre mira a la cámara: fantasmas y, más adelante, objetos y decoraciones
+pub struct Sprite<'a> {
+    pub position: Vec2,
+    pub texture: &'a Texture,
+    pub size: f32,      // Altura en alturas de pared; el ancho sigue la proporción de la textura
+    pub elevation: f32, // Altura sobre el suelo, también en alturas de pared
+    pub opacity: f32,   // Multiplica el alfa de cada texel
+}
+
+// Cómo proyecta la vista 3D: los sprites se escalan igual que las paredes para que encajen con ellas
+pub struct Projection {
+    pub horizon: f32,     // Fila de pantalla del horizonte
+    pub wall_height: f32, // Altura en píxeles de una pared a distancia 1
+}
+
+// Dibuja los sprites de atrás hacia adelante, recortando columna por columna contra `depth`,
+// la distancia perpendicular a la pared de cada columna. `shade` recibe cada texel con la
+// posición del sprite, el píxel (x, y) y la distancia, y devuelve el color final.
+pub fn render_sprites(
+    framebuffer: &mut FramebufferView,
+    player: &Player,
+    sprites: &mut [Sprite],
+    depth: &[f32],
+    projection: &Projection,
+    shade: impl Fn(u32, Vec2, usize, usize, f32) -> u32,
+) {
+    let distance = |sprite: &Sprite| (sprite.position - player.position).norm();
+    sprites.sort_by(|a, b| distance(b).total_cmp(&distance(a)));
+
+    let screen_width = framebuffer.width() as f32;
+    framebuffer.set_blend_mode(BlendMode::Alpha);
+
+    for sprite in sprites.iter() {
+        let delta = sprite.position - player.position;
+        let distance = delta.norm();
+
+        // Ángulo respecto a la mirada del jugador, llevado a -PI..PI
+        let relative_angle = (delta.y.atan2(delta.x) - player.a + PI).rem_euclid(2.0 * PI) - PI;
+        let perpendicular = distance * relative_angle.cos();
+        if perpendicular < 1.0 {
+            continue; // Detrás del jugador o pegado a la cámara
+        }
+
+        // Las columnas de la vista avanzan en ángulos iguales, igual que los rayos de las paredes
+        let center_x = (relative_angle / player.fov + 0.5) * screen_width;
+        let height = sprite.size * projection.wall_height / perpendicular;
+        let width = height * sprite.texture.width as f32 / sprite.texture.height as f32;
+        let left = center_x - width / 2.0;
+        // El sprite se apoya en el suelo, donde termina la pared a su misma distancia, o flota sobre él
+        let bottom = projection.horizon + projection.wall_height / perpendicular * (0.5 - sprite.elevation);
+        let top = bottom - height;
+
+        let x0 = left.max(0.0) as usize;
+        let x1 = ((left + width).max(0.0) as usize).min(framebuffer.width());
+        let y0 = top.max(0.0) as usize;
+        let y1 = (bottom.max(0.0) as usize).min(framebuffer.height());
+
+        for x in x0..x1 {
+            // Una pared más cercana tapa esta columna del sprite
+            if depth.get(x).is_some_and(|wall| *wall < perpendicular) {
+                continue;
+            }
+            let texture_x = ((x as f32 - left) / width * sprite.texture.width as f32) as usize;
+            for y in y0..y1 {
+                let texture_y = ((y as f32 - top) / height * sprite.texture.height as f32) as usize;
+                let texel = sprite.texture.get_rgba(texture_x, texture_y);
+                let alpha = ((texel >> 24) as f32 * sprite.opacity.clamp(0.0, 1.0)) as u32;
+                if alpha == 0 {
+                    continue;
+                }
+                let color = (alpha << 24) | (texel & 0xFFFFFF);
+                framebuffer.set_current_rgba(shade(color, sprite.position, x, y, distance));
+                framebuffer.point(x, y);
+            }
+        }
+    }
+
+    framebuffer.set_blend_mode(BlendMode::Opaque);
+}
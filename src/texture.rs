@@ -1,29 +1,329 @@
-use image::GenericImageView;
+use std::collections::HashMap;
+use image::{GenericImageView, ImageResult};
+
+// Filtro usado al leer la textura entre texels
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Filter {
+    Nearest,  // Texel más cercano
+    Bilinear, // Promedio ponderado de los cuatro texels vecinos
+}
+
+// Qué hacer con las coordenadas que caen fuera de la textura
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WrapMode {
+    Clamp,  // Repetir el texel del borde
+    Repeat, // Repetir la textura
+    Mirror, // Repetir la textura reflejada en cada copia
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Sampler {
+    pub filter: Filter,
+    pub wrap: WrapMode,
+}
+
+impl Sampler {
+    pub const fn new(filter: Filter, wrap: WrapMode) -> Self {
+        Self { filter, wrap }
+    }
+}
+
+// Una resolución de la textura; el nivel 0 es la imagen original
+struct MipLevel {
+    width: usize,
+    height: usize,
+    texels: Vec<u32>, // Colores en formato 0xAARRGGBB
+}
+
+impl MipLevel {
+    fn texel(&self, x: isize, y: isize, wrap: WrapMode) -> u32 {
+        let x = wrap_coordinate(x, self.width, wrap);
+        let y = wrap_coordinate(y, self.height, wrap);
+        self.texels[y * self.width + x]
+    }
+
+    // Nivel con la mitad de resolución, promediando bloques de 2x2 texels
+    fn downsample(&self) -> MipLevel {
+        let width = (self.width / 2).max(1);
+        let height = (self.height / 2).max(1);
+        let mut texels = Vec::with_capacity(width * height);
+
+        for y in 0..height as isize {
+            for x in 0..width as isize {
+                texels.push(average(&[
+                    self.texel(2 * x, 2 * y, WrapMode::Clamp),
+                    self.texel(2 * x + 1, 2 * y, WrapMode::Clamp),
+                    self.texel(2 * x, 2 * y + 1, WrapMode::Clamp),
+                    self.texel(2 * x + 1, 2 * y + 1, WrapMode::Clamp),
+                ]));
+            }
+        }
+
+        MipLevel { width, height, texels }
+    }
+}
+
+fn wrap_coordinate(coordinate: isize, size: usize, wrap: WrapMode) -> usize {
+    let size = size as isize;
+    let wrapped = match wrap {
+        WrapMode::Clamp => coordinate.clamp(0, size - 1),
+        WrapMode::Repeat => coordinate.rem_euclid(size),
+        WrapMode::Mirror => {
+            let period = coordinate.rem_euclid(2 * size);
+            if period < size { period } else { 2 * size - 1 - period }
+        }
+    };
+    wrapped as usize
+}
+
+fn average(colors: &[u32]) -> u32 {
+    [24, 16, 8, 0].iter().fold(0, |color, shift| {
+        let sum: u32 = colors.iter().map(|c| (c >> shift) & 0xFF).sum();
+        color | ((sum / colors.len() as u32) << shift)
+    })
+}
+
+fn lerp_color(a: u32, b: u32, t: f32) -> u32 {
+    [24, 16, 8, 0].iter().fold(0, |color, shift| {
+        let from = ((a >> shift) & 0xFF) as f32;
+        let to = ((b >> shift) & 0xFF) as f32;
+        color | (((from + (to - from) * t) as u32) << shift)
+    })
+}
 
 pub struct Texture {
     pub width: usize,
     pub height: usize,
-    pub pixels: Vec<u8>,
+    levels: Vec<MipLevel>, // Cadena de mipmaps, cada nivel a la mitad del anterior hasta 1x1
 }
 
 impl Texture {
-    pub fn from_file(path: &str) -> Self {
-        let img = image::open(path).expect("Failed to load texture");
+    pub fn from_file(path: &str) -> ImageResult<Self> {
+        let img = image::open(path)?;
         let (width, height) = img.dimensions();
-        let pixels = img.to_rgba8().into_raw();
+        let texels = img
+            .to_rgba8()
+            .pixels()
+            .map(|p| {
+                let [r, g, b, a] = p.0;
+                ((a as u32) << 24) | ((r as u32) << 16) | ((g as u32) << 8) | b as u32
+            })
+            .collect();
+
+        Ok(Texture::from_texels(width as usize, height as usize, texels))
+    }
+
+    // Tablero magenta y negro que reemplaza a las texturas que no se pudieron cargar
+    pub fn checkerboard() -> Self {
+        let size = 64;
+        let texels = (0..size * size)
+            .map(|i| if ((i % size) / 8 + (i / size) / 8) % 2 == 0 { 0xFFFF00FF } else { 0xFF000000 })
+            .collect();
+        Texture::from_texels(size, size, texels)
+    }
 
-        Texture {
-            width: width as usize,
-            height: height as usize,
-            pixels,
+    // Crea la textura a partir de colores 0xAARRGGBB fila por fila y precalcula sus mipmaps
+    pub fn from_texels(width: usize, height: usize, texels: Vec<u32>) -> Self {
+        let mut levels = vec![MipLevel { width, height, texels }];
+        while let Some(last) = levels.last().filter(|level| level.width > 1 || level.height > 1) {
+            let next = last.downsample();
+            levels.push(next);
         }
+
+        Texture { width, height, levels }
+    }
+
+    // Color del texel en formato 0xAARRGGBB; las coordenadas fuera de rango se ajustan al borde
+    pub fn get_rgba(&self, x: usize, y: usize) -> u32 {
+        let x = x.min(self.width - 1) as isize;
+        let y = y.min(self.height - 1) as isize;
+        self.levels[0].texel(x, y, WrapMode::Clamp)
     }
 
-    pub fn get_pixel(&self, x: usize, y: usize) -> u32 {
-        let index = (y * self.width + x) * 4;
-        let r = self.pixels[index] as u32;
-        let g = self.pixels[index + 1] as u32;
-        let b = self.pixels[index + 2] as u32;
-        (r << 16) | (g << 8) | b
+    // Nivel de mipmap adecuado cuando un píxel de pantalla cubre `texels_per_pixel` texels
+    pub fn mip_level_for(&self, texels_per_pixel: f32) -> usize {
+        if texels_per_pixel <= 1.0 {
+            return 0;
+        }
+        (texels_per_pixel.log2() as usize).min(self.levels.len() - 1)
+    }
+
+    // Color 0xAARRGGBB en coordenadas normalizadas: (0, 0) es la esquina superior izquierda
+    // y (1, 1) la inferior derecha; fuera de ese rango manda el modo de envoltura
+    pub fn sample(&self, u: f32, v: f32, sampler: Sampler, level: usize) -> u32 {
+        let mip = &self.levels[level.min(self.levels.len() - 1)];
+        let x = u * mip.width as f32;
+        let y = v * mip.height as f32;
+
+        match sampler.filter {
+            Filter::Nearest => mip.texel(x.floor() as isize, y.floor() as isize, sampler.wrap),
+            Filter::Bilinear => {
+                // Los centros de los texels están en las coordenadas +0.5
+                let (x, y) = (x - 0.5, y - 0.5);
+                let (x0, y0) = (x.floor(), y.floor());
+                let (tx, ty) = (x - x0, y - y0);
+                let (x0, y0) = (x0 as isize, y0 as isize);
+
+                let top = lerp_color(
+                    mip.texel(x0, y0, sampler.wrap),
+                    mip.texel(x0 + 1, y0, sampler.wrap),
+                    tx,
+                );
+                let bottom = lerp_color(
+                    mip.texel(x0, y0 + 1, sampler.wrap),
+                    mip.texel(x0 + 1, y0 + 1, sampler.wrap),
+                    tx,
+                );
+                lerp_color(top, bottom, ty)
+            }
+        }
     }
-}
\ No newline at end of file
+}
+
+// Coordenada normalizada llevada a [0, 1] según el modo de envoltura
+fn wrap_unit(t: f32, wrap: WrapMode) -> f32 {
+    match wrap {
+        WrapMode::Clamp => t.clamp(0.0, 1.0),
+        WrapMode::Repeat => t.rem_euclid(1.0),
+        WrapMode::Mirror => {
+            let period = t.rem_euclid(2.0);
+            if period <= 1.0 { period } else { 2.0 - period }
+        }
+    }
+}
+
+// Varias texturas empaquetadas en una sola fila de casillas cuadradas del mismo tamaño,
+// indexadas por el carácter de la leyenda del nivel
+pub struct TextureAtlas {
+    texture: Texture,
+    pub tile_size: usize,     // Lado de cada casilla en texels; potencia de dos
+    tiles: HashMap<char, usize>, // Carácter -> columna de su casilla
+}
+
+impl TextureAtlas {
+    // Cada textura se reescala a la casilla; el lado es la mayor dimensión redondeada
+    // a potencia de dos para que los mipmaps no mezclen casillas vecinas
+    pub fn pack(textures: &[(char, &Texture)]) -> Self {
+        let tile_size = textures
+            .iter()
+            .map(|(_, texture)| texture.width.max(texture.height))
+            .max()
+            .unwrap_or(1)
+            .next_power_of_two()
+            .min(1024);
+        let columns = textures.len().max(1);
+        let width = tile_size * columns;
+        let mut texels = vec![0; width * tile_size];
+        let resample = Sampler::new(Filter::Bilinear, WrapMode::Clamp);
+
+        for (column, (_, texture)) in textures.iter().enumerate() {
+            let level = texture.mip_level_for(texture.width.max(texture.height) as f32 / tile_size as f32);
+            for y in 0..tile_size {
+                for x in 0..tile_size {
+                    let u = (x as f32 + 0.5) / tile_size as f32;
+                    let v = (y as f32 + 0.5) / tile_size as f32;
+                    texels[y * width + column * tile_size + x] = texture.sample(u, v, resample, level);
+                }
+            }
+        }
+
+        Self {
+            texture: Texture::from_texels(width, tile_size, texels),
+            tile_size,
+            tiles: textures.iter().enumerate().map(|(column, (glyph, _))| (*glyph, column)).collect(),
+        }
+    }
+
+    // Igual que en `Texture`, pero sin bajar de una casilla de 1x1
+    pub fn mip_level_for(&self, texels_per_pixel: f32) -> usize {
+        self.texture.mip_level_for(texels_per_pixel).min(self.tile_size.trailing_zeros() as usize)
+    }
+
+    // Muestra la casilla de un carácter como si fuera una textura independiente;
+    // `None` si el carácter no tiene textura en el atlas
+    pub fn sample(&self, glyph: char, u: f32, v: f32, sampler: Sampler, level: usize) -> Option<u32> {
+        let column = *self.tiles.get(&glyph)?;
+
+        // Mantener la muestra medio texel dentro de la casilla para que el filtro no lea las vecinas
+        let tile_texels = (self.tile_size >> level).max(1) as f32;
+        let margin = 0.5 / tile_texels;
+        let u = wrap_unit(u, sampler.wrap).clamp(margin, 1.0 - margin);
+        let v = wrap_unit(v, sampler.wrap).clamp(margin, 1.0 - margin);
+
+        let columns = self.tiles.len() as f32;
+        let clamp = Sampler::new(sampler.filter, WrapMode::Clamp);
+        Some(self.texture.sample((column as f32 + u) / columns, v, clamp, level))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLACK: u32 = 0xFF000000;
+    const WHITE: u32 = 0xFFFFFFFF;
+    const GREY: u32 = 0xFF7F7F7F;
+    const RED: u32 = 0xFFFF0000;
+    const BLUE: u32 = 0xFF0000FF;
+
+    fn bilinear(wrap: WrapMode) -> Sampler {
+        Sampler::new(Filter::Bilinear, wrap)
+    }
+
+    #[test]
+    fn wrap_coordinate_outside_the_texture() {
+        let wrap = |x, mode| wrap_coordinate(x, 4, mode);
+        assert_eq!([-3, 0, 3, 7].map(|x| wrap(x, WrapMode::Clamp)), [0, 0, 3, 3]);
+        assert_eq!([-5, -1, 4, 5].map(|x| wrap(x, WrapMode::Repeat)), [3, 3, 0, 1]);
+        // El borde se repite al reflejar: ... 1 0 | 0 1 2 3 | 3 2 ...
+        assert_eq!([-5, -2, -1, 4, 5, 8].map(|x| wrap(x, WrapMode::Mirror)), [3, 1, 0, 3, 2, 0]);
+    }
+
+    #[test]
+    fn wrap_unit_outside_the_texture() {
+        assert_eq!([-0.5, 0.25, 1.5].map(|t| wrap_unit(t, WrapMode::Clamp)), [0.0, 0.25, 1.0]);
+        assert_eq!([-0.25, 1.25, 3.5].map(|t| wrap_unit(t, WrapMode::Repeat)), [0.75, 0.25, 0.5]);
+        assert_eq!([-0.25, 1.25, 2.5].map(|t| wrap_unit(t, WrapMode::Mirror)), [0.25, 0.75, 0.5]);
+    }
+
+    #[test]
+    fn bilinear_sample_blends_neighbors_by_wrap_mode() {
+        let texture = Texture::from_texels(2, 1, vec![BLACK, WHITE]);
+
+        // En los centros de los texels no hay mezcla; entre ellos, mitad y mitad
+        assert_eq!(texture.sample(0.25, 0.5, bilinear(WrapMode::Clamp), 0), BLACK);
+        assert_eq!(texture.sample(0.75, 0.5, bilinear(WrapMode::Clamp), 0), WHITE);
+        assert_eq!(texture.sample(0.5, 0.5, bilinear(WrapMode::Clamp), 0), GREY);
+
+        // Fuera de la textura
+        assert_eq!(texture.sample(-1.0, 0.5, bilinear(WrapMode::Clamp), 0), BLACK);
+        assert_eq!(texture.sample(2.0, 0.5, bilinear(WrapMode::Clamp), 0), WHITE);
+        assert_eq!(texture.sample(-0.75, 0.5, bilinear(WrapMode::Repeat), 0), BLACK);
+        assert_eq!(texture.sample(-0.25, 0.5, bilinear(WrapMode::Mirror), 0), BLACK);
+        assert_eq!(texture.sample(1.25, 0.5, bilinear(WrapMode::Mirror), 0), WHITE);
+
+        // En el borde, repeat mezcla con el lado opuesto y mirror con el mismo texel
+        assert_eq!(texture.sample(1.0, 0.5, bilinear(WrapMode::Repeat), 0), GREY);
+        assert_eq!(texture.sample(1.0, 0.5, bilinear(WrapMode::Mirror), 0), WHITE);
+        assert_eq!(texture.sample(0.0, 0.5, bilinear(WrapMode::Clamp), 0), BLACK);
+    }
+
+    #[test]
+    fn atlas_samples_stay_inside_their_tile() {
+        let red = Texture::from_texels(2, 2, vec![RED; 4]);
+        let blue = Texture::from_texels(2, 2, vec![BLUE; 4]);
+        let atlas = TextureAtlas::pack(&[('a', &red), ('b', &blue)]);
+        assert_eq!(atlas.tile_size, 2);
+
+        // Los bordes de cada casilla no leen la vecina, con cualquier modo y nivel de mipmap
+        for wrap in [WrapMode::Clamp, WrapMode::Repeat, WrapMode::Mirror] {
+            for level in 0..=1 {
+                for u in [-0.01, 0.0, 0.5, 0.999, 1.0, 1.01] {
+                    assert_eq!(atlas.sample('a', u, 0.5, bilinear(wrap), level), Some(RED));
+                    assert_eq!(atlas.sample('b', u, 0.5, bilinear(wrap), level), Some(BLUE));
+                }
+            }
+        }
+        assert_eq!(atlas.sample('c', 0.5, 0.5, bilinear(WrapMode::Clamp), 0), None);
+    }
+}
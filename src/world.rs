@@ -0,0 +1,57 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+use crate::animation::SpriteSheet;
+use crate::assets::AssetManager;
+use crate::lighting::Lightmap;
+use crate::maze::Level;
+use crate::texture::{Texture, TextureAtlas};
+
+// Contexto compartido por la lógica y los renderizadores: el nivel actual y sus texturas,
+// para que la vista 3D, la 2D, el minimapa y las colisiones usen siempre el mismo laberinto
+pub struct World {
+    pub level: Level,
+    pub textures: HashMap<char, Rc<Texture>>, // Texturas de pared según la leyenda del nivel
+    pub atlas: TextureAtlas,                  // Las mismas texturas empaquetadas para la vista 3D
+    pub floor_textures: HashMap<char, Rc<Texture>>,
+    pub ceiling_textures: HashMap<char, Rc<Texture>>,
+    pub wall_sheets: HashMap<char, Rc<SpriteSheet>>, // Paredes animadas, como antorchas
+    pub lightmap: Lightmap, // Luz de cada celda según las fuentes del nivel
+    pub time: f32,          // Segundos desde que se cargó el nivel, para las animaciones
+}
+
+impl World {
+    pub fn new(level: Level, assets: &mut AssetManager) -> Self {
+        let textures = assets.legend_textures(&level.legend);
+        let atlas = pack_atlas(&textures);
+        let floor_textures = assets.legend_textures(&level.floor_legend);
+        let ceiling_textures = assets.legend_textures(&level.ceiling_legend);
+        let wall_sheets = assets.legend_sheets(&level.legend);
+        let lightmap = Lightmap::new(&level);
+
+        Self { level, textures, atlas, floor_textures, ceiling_textures, wall_sheets, lightmap, time: 0.0 }
+    }
+
+    // Vuelve a tomar las texturas de las leyendas del gestor de recursos y rehace el atlas
+    pub fn refresh_textures(&mut self, assets: &mut AssetManager) {
+        self.textures = assets.legend_textures(&self.level.legend);
+        self.atlas = pack_atlas(&self.textures);
+        self.floor_textures = assets.legend_textures(&self.level.floor_legend);
+        self.ceiling_textures = assets.legend_textures(&self.level.ceiling_legend);
+        self.wall_sheets = assets.legend_sheets(&self.level.legend);
+    }
+
+    pub fn maze(&self) -> &[Vec<char>] {
+        &self.level.grid
+    }
+
+    pub fn block_size(&self) -> usize {
+        self.level.block_size
+    }
+}
+
+fn pack_atlas(textures: &HashMap<char, Rc<Texture>>) -> TextureAtlas {
+    // Ordenar por carácter para que el atlas no dependa del orden del HashMap
+    let mut tiles: Vec<(char, &Texture)> = textures.iter().map(|(glyph, texture)| (*glyph, texture.as_ref())).collect();
+    tiles.sort_by_key(|(glyph, _)| *glyph);
+    TextureAtlas::pack(&tiles)
+}